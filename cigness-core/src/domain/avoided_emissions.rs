@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::carbon_model::CarbonFactors;
+use crate::domain::smoker_journey::SmokerJourney;
+
+/// A point-in-time consumption reading, e.g. a profile revision or a daily
+/// session-shard total. The reading is assumed to hold until the next one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumptionObservation {
+    pub at: DateTime<Utc>,
+    pub packs_per_day: f32,
+}
+
+impl ConsumptionObservation {
+    /// Build an observation from a daily cigarette count, as reported in
+    /// session shards (`cigarettes_today`).
+    pub fn from_cigarettes_per_day(
+        at: DateTime<Utc>,
+        cigarettes_per_day: f32,
+        cigarettes_per_pack: u32,
+    ) -> Self {
+        ConsumptionObservation {
+            at,
+            packs_per_day: cigarettes_per_day.max(0.0) / cigarettes_per_pack.max(1) as f32,
+        }
+    }
+}
+
+/// Low/central/high factor sets bounding the LCA uncertainty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonFactorRange {
+    pub low: CarbonFactors,
+    pub central: CarbonFactors,
+    pub high: CarbonFactors,
+}
+
+/// Cumulative avoided CO₂e at the time of one observation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvoidedEmissionsPoint {
+    pub at: DateTime<Utc>,
+    pub cumulative_kg_co2e_low: f32,
+    pub cumulative_kg_co2e_central: f32,
+    pub cumulative_kg_co2e_high: f32,
+}
+
+/// Emissions avoided relative to a counterfactual where the person kept
+/// smoking at their enrollment baseline.
+pub struct AvoidedEmissionsCalculator;
+
+impl AvoidedEmissionsCalculator {
+    /// Integrate (baseline − observed) packs/day over time. Each observation
+    /// holds until the next; the series ends at the last observation.
+    /// Periods above baseline count negatively, so relapse reduces the total;
+    /// the low and high bounds stay ordered when the total is negative.
+    pub fn compute(
+        baseline_packs_per_day: f32,
+        observations: &[ConsumptionObservation],
+        factors: &CarbonFactorRange,
+    ) -> Vec<AvoidedEmissionsPoint> {
+        let baseline = baseline_packs_per_day.max(0.0);
+        let mut sorted: Vec<&ConsumptionObservation> = observations.iter().collect();
+        sorted.sort_by_key(|o| o.at);
+
+        let per_pack = |f: &CarbonFactors| {
+            (f.kg_co2e_per_cigarette + f.kg_co2e_per_butt_waste)
+                * f.cigarettes_per_pack.max(1) as f32
+        };
+        let (low, central, high) = (
+            per_pack(&factors.low),
+            per_pack(&factors.central),
+            per_pack(&factors.high),
+        );
+
+        let mut avoided_packs = 0.0_f32;
+        let mut out = Vec::with_capacity(sorted.len());
+        for (i, obs) in sorted.iter().enumerate() {
+            if i > 0 {
+                let prev = sorted[i - 1];
+                let days = (obs.at - prev.at).num_seconds() as f32 / 86_400.0;
+                avoided_packs += (baseline - prev.packs_per_day.max(0.0)) * days;
+            }
+            let (a, b) = (avoided_packs * low, avoided_packs * high);
+            out.push(AvoidedEmissionsPoint {
+                at: obs.at,
+                cumulative_kg_co2e_low: a.min(b),
+                cumulative_kg_co2e_central: avoided_packs * central,
+                cumulative_kg_co2e_high: a.max(b),
+            });
+        }
+        out
+    }
+
+    /// Use a journey's first revision as the baseline and every revision
+    /// as an observation.
+    pub fn from_journey(
+        journey: &SmokerJourney,
+        factors: &CarbonFactorRange,
+    ) -> Vec<AvoidedEmissionsPoint> {
+        let baseline = match journey.baseline() {
+            Some(b) => b.packs_per_day,
+            None => return Vec::new(),
+        };
+        let observations: Vec<ConsumptionObservation> = journey
+            .revisions()
+            .iter()
+            .map(|r| ConsumptionObservation {
+                at: r.updated_at_utc,
+                packs_per_day: r.packs_per_day,
+            })
+            .collect();
+        Self::compute(baseline, &observations, factors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn factors(kg_co2e_per_cigarette: f32) -> CarbonFactors {
+        CarbonFactors {
+            kg_co2e_per_cigarette,
+            cigarettes_per_pack: 20,
+            kg_co2e_per_butt_waste: 0.0,
+            product_factors: Vec::new(),
+        }
+    }
+
+    fn range() -> CarbonFactorRange {
+        CarbonFactorRange {
+            low: factors(0.01),
+            central: factors(0.014),
+            high: factors(0.02),
+        }
+    }
+
+    fn observation(day: u32, packs_per_day: f32) -> ConsumptionObservation {
+        ConsumptionObservation {
+            at: Utc.with_ymd_and_hms(2026, 3, day, 0, 0, 0).unwrap(),
+            packs_per_day,
+        }
+    }
+
+    #[test]
+    fn test_reduction_is_positive_and_ordered() {
+        let points = AvoidedEmissionsCalculator::compute(
+            1.0,
+            &[observation(1, 0.5), observation(11, 0.5)],
+            &range(),
+        );
+        let last = points.last().unwrap();
+        // 0.5 packs/day avoided for 10 days at 0.28 kg CO2e per pack.
+        assert!((last.cumulative_kg_co2e_central - 1.4).abs() < 1e-4);
+        assert!(last.cumulative_kg_co2e_low < last.cumulative_kg_co2e_central);
+        assert!(last.cumulative_kg_co2e_central < last.cumulative_kg_co2e_high);
+    }
+
+    #[test]
+    fn test_smoking_above_baseline_keeps_bounds_ordered() {
+        let points = AvoidedEmissionsCalculator::compute(
+            1.0,
+            &[observation(1, 2.0), observation(11, 2.0)],
+            &range(),
+        );
+        let last = points.last().unwrap();
+        assert!(last.cumulative_kg_co2e_central < 0.0);
+        assert!(last.cumulative_kg_co2e_low <= last.cumulative_kg_co2e_central);
+        assert!(last.cumulative_kg_co2e_central <= last.cumulative_kg_co2e_high);
+    }
+}
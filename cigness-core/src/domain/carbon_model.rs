@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::nicotine_product::{NicotineProduct, ProductFootprintFactor, ProductUsage};
+
 /// CarbonModel calculates per-smoker and aggregated CO₂-equivalent emissions
 /// from real smoking patterns, using configurable factors.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +12,10 @@ pub struct CarbonFactors {
     pub cigarettes_per_pack: u32,
     /// CO₂e per disposed cigarette butt (kg), including cleanup logistics.
     pub kg_co2e_per_butt_waste: f32,
+    /// Per-unit factors for non-cigarette products. Products without an
+    /// entry contribute no emissions.
+    #[serde(default)]
+    pub product_factors: Vec<ProductFootprintFactor>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,4 +48,172 @@ impl CarbonFactors {
             annual_kg_co2e_total,
         }
     }
+
+    /// Compute annual footprint across all products in use. Cigarettes
+    /// (in packs/day) use the per-cigarette factors; other products use
+    /// `product_factors`.
+    pub fn compute_annual_footprint_for_products(
+        &self,
+        usage: &[ProductUsage],
+    ) -> SmokerCarbonFootprint {
+        let days_per_year = 365.0;
+        let mut annual_kg_co2e_combustion = 0.0_f32;
+        let mut annual_kg_co2e_waste = 0.0_f32;
+
+        for u in usage {
+            if u.product == NicotineProduct::Cigarette {
+                let cigarettes = self.compute_annual_footprint(u.units_per_day);
+                annual_kg_co2e_combustion += cigarettes.annual_kg_co2e_combustion;
+                annual_kg_co2e_waste += cigarettes.annual_kg_co2e_waste;
+                continue;
+            }
+            if let Some(f) = self.product_factor(u.product) {
+                let annual_units = u.units_per_day.max(0.0) * days_per_year;
+                annual_kg_co2e_combustion += annual_units * f.kg_co2e_per_unit;
+                annual_kg_co2e_waste += annual_units * f.kg_co2e_waste_per_unit;
+            }
+        }
+
+        SmokerCarbonFootprint {
+            annual_kg_co2e_combustion,
+            annual_kg_co2e_waste,
+            annual_kg_co2e_total: annual_kg_co2e_combustion + annual_kg_co2e_waste,
+        }
+    }
+
+    pub fn product_factor(&self, product: NicotineProduct) -> Option<&ProductFootprintFactor> {
+        self.product_factors.iter().find(|f| f.product == product)
+    }
+}
+
+/// Per-cigarette emission and waste factors beyond CO₂e, following the
+/// M = (CO₂e, PM2.5, NOx, butt waste, …) vector in the district pilot
+/// protocol. Water and butt mass align with tobacco_footprint_factors_v1.csv.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollutantFactors {
+    /// PM2.5 emitted per cigarette (g).
+    pub g_pm25_per_cigarette: f32,
+    /// NOx emitted per cigarette (g).
+    pub g_nox_per_cigarette: f32,
+    /// Lifecycle water use per cigarette (L).
+    pub l_water_per_cigarette: f32,
+    /// Butt mass discarded per cigarette (kg).
+    pub kg_butt_per_cigarette: f32,
+}
+
+/// Multi-metric footprint; each field name carries its unit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FootprintVector {
+    pub kg_co2e: f32,
+    pub g_pm25: f32,
+    pub g_nox: f32,
+    pub l_water: f32,
+    pub kg_butt_waste: f32,
+}
+
+impl FootprintVector {
+    pub fn scaled(&self, factor: f32) -> FootprintVector {
+        FootprintVector {
+            kg_co2e: self.kg_co2e * factor,
+            g_pm25: self.g_pm25 * factor,
+            g_nox: self.g_nox * factor,
+            l_water: self.l_water * factor,
+            kg_butt_waste: self.kg_butt_waste * factor,
+        }
+    }
+
+    /// (metric, unit, value) triples for reporting.
+    pub fn components(&self) -> [(&'static str, &'static str, f32); 5] {
+        [
+            ("CO2e", "kg", self.kg_co2e),
+            ("PM2.5", "g", self.g_pm25),
+            ("NOx", "g", self.g_nox),
+            ("water", "L", self.l_water),
+            ("butt_waste", "kg", self.kg_butt_waste),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SmokingSetting {
+    Indoor,
+    Outdoor,
+}
+
+/// Sensitive receptors named in the pilot protocol's λ-fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SensitiveReceptor {
+    School,
+    Clinic,
+}
+
+/// Where a smoking event happens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventContext {
+    pub setting: SmokingSetting,
+    /// Nearby sensitive receptor, if any.
+    pub sensitive_receptor: Option<SensitiveReceptor>,
+    /// True for blocks flagged as over-burdened by environmental justice data.
+    pub overburdened_block: bool,
+}
+
+/// Context multipliers (λ) applied to the local-exposure metrics PM2.5 and
+/// NOx. CO₂e, water and butt waste are not location dependent and are
+/// never adjusted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextAdjustments {
+    pub indoor_multiplier: f32,
+    pub sensitive_receptor_multiplier: f32,
+    pub overburdened_multiplier: f32,
+}
+
+impl ContextAdjustments {
+    pub fn exposure_multiplier(&self, ctx: &EventContext) -> f32 {
+        let mut m = 1.0_f32;
+        if ctx.setting == SmokingSetting::Indoor {
+            m *= self.indoor_multiplier.max(1.0);
+        }
+        if ctx.sensitive_receptor.is_some() {
+            m *= self.sensitive_receptor_multiplier.max(1.0);
+        }
+        if ctx.overburdened_block {
+            m *= self.overburdened_multiplier.max(1.0);
+        }
+        m
+    }
+}
+
+impl CarbonFactors {
+    /// Footprint of a single cigarette smoked in `ctx`.
+    pub fn compute_event_footprint(
+        &self,
+        pollutants: &PollutantFactors,
+        adjustments: &ContextAdjustments,
+        ctx: &EventContext,
+    ) -> FootprintVector {
+        let exposure = adjustments.exposure_multiplier(ctx);
+
+        FootprintVector {
+            kg_co2e: (self.kg_co2e_per_cigarette + self.kg_co2e_per_butt_waste).max(0.0),
+            g_pm25: pollutants.g_pm25_per_cigarette.max(0.0) * exposure,
+            g_nox: pollutants.g_nox_per_cigarette.max(0.0) * exposure,
+            l_water: pollutants.l_water_per_cigarette.max(0.0),
+            kg_butt_waste: pollutants.kg_butt_per_cigarette.max(0.0),
+        }
+    }
+
+    /// Annualized footprint from average packs per day, assuming every
+    /// cigarette is smoked in `ctx`.
+    pub fn compute_annual_footprint_vector(
+        &self,
+        pollutants: &PollutantFactors,
+        adjustments: &ContextAdjustments,
+        ctx: &EventContext,
+        packs_per_day: f32,
+    ) -> FootprintVector {
+        let cigarettes_per_year =
+            packs_per_day.max(0.0) * self.cigarettes_per_pack.max(1) as f32 * 365.0;
+        self.compute_event_footprint(pollutants, adjustments, ctx)
+            .scaled(cigarettes_per_year)
+    }
 }
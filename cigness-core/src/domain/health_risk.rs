@@ -1,6 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::domain::smoker_profile::{HealthRiskBand, SmokerDerivedMetrics};
+use crate::domain::nicotine_product::NicotineEquivalence;
+use crate::domain::smoker_profile::{HealthRiskBand, SmokerDerivedMetrics, SmokerProfile};
+
+/// Pack-years at or above which escalation is required on exposure alone.
+const ESCALATION_PACK_YEARS: f32 = 20.0;
 
 /// Structured health risk report aligned with real-world clinical concerns.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,19 +14,169 @@ pub struct HealthRiskReport {
     pub risk_band: HealthRiskBand,
     /// Binary flags for urgent escalation.
     pub escalation_required: bool,
+    /// Why escalation was triggered; empty when no escalation is required.
+    pub escalation_reasons: Vec<EscalationReason>,
+    /// How quickly a clinician should follow up.
+    pub urgency: EscalationUrgency,
+}
+
+/// Clinical trigger behind an escalation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscalationReason {
+    /// Physician-confirmed smoking-related condition (e.g., COPD).
+    DiagnosedCondition,
+    /// Cumulative exposure at or above the pack-years threshold.
+    PackYearsThreshold,
+    /// Self-reported severe anxiety or tobacco shakes.
+    SevereAnxiety,
+    /// A clinician has already recommended cessation.
+    ClinicianRecommendation,
+}
+
+/// Recommended follow-up urgency, ordered from least to most urgent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EscalationUrgency {
+    /// No clinician follow-up beyond normal monitoring.
+    Routine,
+    /// Follow up within roughly two weeks.
+    Soon,
+    /// Follow up within a few days.
+    Urgent,
+}
+
+impl EscalationReason {
+    fn urgency(&self) -> EscalationUrgency {
+        match self {
+            EscalationReason::DiagnosedCondition => EscalationUrgency::Urgent,
+            EscalationReason::SevereAnxiety => EscalationUrgency::Urgent,
+            EscalationReason::PackYearsThreshold => EscalationUrgency::Soon,
+            EscalationReason::ClinicianRecommendation => EscalationUrgency::Soon,
+        }
+    }
+
+    fn recommended_action(&self) -> &'static str {
+        match self {
+            EscalationReason::DiagnosedCondition => {
+                "Coordinate cessation with the treating physician for the diagnosed condition."
+            }
+            EscalationReason::PackYearsThreshold => {
+                "Review eligibility for lung cancer screening and structured counseling."
+            }
+            EscalationReason::SevereAnxiety => {
+                "Assess withdrawal and anxiety symptoms before further tapering."
+            }
+            EscalationReason::ClinicianRecommendation => {
+                "Confirm the existing clinician recommendation and align the plan with it."
+            }
+        }
+    }
 }
 
 impl HealthRiskReport {
+    /// Build a report from derived metrics only. Reasons that need the full
+    /// profile (anxiety, clinician recommendation) are not detected here;
+    /// use `from_profile` when the profile is available.
     pub fn from_derived(metrics: &SmokerDerivedMetrics) -> Self {
-        let escalation_required = matches!(
-            metrics.risk_band,
-            HealthRiskBand::High | HealthRiskBand::Critical
-        );
+        let mut reasons = Vec::new();
+        if matches!(metrics.risk_band, HealthRiskBand::Critical) {
+            reasons.push(EscalationReason::DiagnosedCondition);
+        }
+        if metrics.pack_years >= ESCALATION_PACK_YEARS {
+            reasons.push(EscalationReason::PackYearsThreshold);
+        }
+
+        Self::with_reasons(metrics, reasons)
+    }
+
+    /// Build a report from a full profile, detecting every escalation reason.
+    pub fn from_profile(profile: &SmokerProfile) -> Self {
+        Self::from_profile_metrics(profile, &profile.derive_metrics())
+    }
+
+    /// Like `from_profile`, but pack-years count every nicotine product the
+    /// person uses, converted to pack-equivalents.
+    pub fn from_profile_with(profile: &SmokerProfile, equivalence: &NicotineEquivalence) -> Self {
+        Self::from_profile_metrics(profile, &profile.derive_metrics_with(equivalence))
+    }
+
+    fn from_profile_metrics(profile: &SmokerProfile, metrics: &SmokerDerivedMetrics) -> Self {
+        let mut reasons = Vec::new();
+        if profile.has_diagnosed_condition {
+            reasons.push(EscalationReason::DiagnosedCondition);
+        }
+        if metrics.pack_years >= ESCALATION_PACK_YEARS {
+            reasons.push(EscalationReason::PackYearsThreshold);
+        }
+        if profile.reports_severe_anxiety {
+            reasons.push(EscalationReason::SevereAnxiety);
+        }
+        if profile.clinician_recommended_cessation {
+            reasons.push(EscalationReason::ClinicianRecommendation);
+        }
+
+        Self::with_reasons(metrics, reasons)
+    }
+
+    fn with_reasons(metrics: &SmokerDerivedMetrics, reasons: Vec<EscalationReason>) -> Self {
+        let urgency = reasons
+            .iter()
+            .map(EscalationReason::urgency)
+            .max()
+            .unwrap_or(EscalationUrgency::Routine);
 
         HealthRiskReport {
             pack_years: metrics.pack_years,
             risk_band: metrics.risk_band.clone(),
-            escalation_required,
+            escalation_required: !reasons.is_empty(),
+            escalation_reasons: reasons,
+            urgency,
         }
     }
+
+    /// Produce a hand-off document for a referral partner, or None if no
+    /// escalation is required. `referral_ref` must be an opaque reference
+    /// agreed with the partner, never the raw `profile_id`.
+    pub fn clinician_handoff(
+        &self,
+        referral_ref: String,
+        issued_at: DateTime<Utc>,
+    ) -> Option<ClinicianHandoff> {
+        if !self.escalation_required {
+            return None;
+        }
+
+        Some(ClinicianHandoff {
+            resource_type: HANDOFF_RESOURCE_TYPE.to_string(),
+            referral_ref,
+            issued_at,
+            risk_band: self.risk_band.clone(),
+            pack_years: (self.pack_years * 10.0).round() / 10.0,
+            reasons: self.escalation_reasons.clone(),
+            urgency: self.urgency,
+            recommended_actions: self
+                .escalation_reasons
+                .iter()
+                .map(|r| r.recommended_action().to_string())
+                .collect(),
+        })
+    }
+}
+
+const HANDOFF_RESOURCE_TYPE: &str = "CignessClinicianHandoff";
+
+/// FHIR-like, locally defined referral document. Carries clinical context
+/// only; it contains no names, contact details or internal profile ids.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClinicianHandoff {
+    pub resource_type: String,
+    /// Opaque reference the partner uses to reconcile with Cigness.
+    pub referral_ref: String,
+    pub issued_at: DateTime<Utc>,
+    pub risk_band: HealthRiskBand,
+    /// Pack-years rounded to one decimal place.
+    pub pack_years: f32,
+    pub reasons: Vec<EscalationReason>,
+    pub urgency: EscalationUrgency,
+    /// Plain-language next steps, one per reason.
+    pub recommended_actions: Vec<String>,
 }
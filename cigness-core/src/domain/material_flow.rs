@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::plastic_recycling::{
+    DevicePlasticBatch, PlasticStream, PlasticType, StageMassLosses,
+};
+
+/// Stage yields (0.0–1.0) and shell suitability for one resin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResinProcessProfile {
+    pub plastic_type: PlasticType,
+    /// Fraction retained after optical/manual sorting.
+    pub sorting_yield: f32,
+    /// Fraction retained after washing.
+    pub washing_yield: f32,
+    /// Fraction retained after pelletizing.
+    pub pelletizing_yield: f32,
+    /// Whether pellets of this resin meet device-shell specifications.
+    pub suitable_for_device_shells: bool,
+}
+
+/// Intake limits from `inputs.quality_requirements` in m_node_profile.cigness.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityRequirements {
+    pub max_contaminant_percent: f32,
+    pub moisture_threshold_percent: f32,
+}
+
+impl QualityRequirements {
+    /// Streams without measurements are accepted; measured values must be
+    /// within limits.
+    pub fn accepts(&self, stream: &PlasticStream) -> bool {
+        let contaminant_ok = stream
+            .contaminant_percent
+            .is_none_or(|c| c <= self.max_contaminant_percent);
+        let moisture_ok = stream
+            .moisture_percent
+            .is_none_or(|m| m <= self.moisture_threshold_percent);
+        contaminant_ok && moisture_ok
+    }
+}
+
+/// Mass balance from collected streams to shell-grade pellets at an M-node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterialFlowModel {
+    pub resins: Vec<ResinProcessProfile>,
+    pub quality: QualityRequirements,
+}
+
+impl MaterialFlowModel {
+    pub fn resin_profile(&self, plastic_type: PlasticType) -> Option<&ResinProcessProfile> {
+        self.resins.iter().find(|r| r.plastic_type == plastic_type)
+    }
+
+    /// True if `stream` passes quality screening and its resin is suitable
+    /// for device shells, i.e. `build_batch` processes it rather than
+    /// counting it as a loss.
+    pub fn accepts_for_shells(&self, stream: &PlasticStream) -> bool {
+        self.quality.accepts(stream)
+            && self
+                .resin_profile(stream.plastic_type)
+                .is_some_and(|p| p.suitable_for_device_shells)
+    }
+
+    /// Run streams through quality screening, resin suitability, sorting,
+    /// washing and pelletizing, then blend in `virgin_input_kg` of virgin
+    /// resin. Resins without a process profile are treated as unsuitable.
+    pub fn build_batch(
+        &self,
+        batch_id: String,
+        streams: &[PlasticStream],
+        virgin_input_kg: f32,
+        kg_per_device_shell: f32,
+    ) -> DevicePlasticBatch {
+        let mut losses = StageMassLosses::default();
+        let mut recycled_pellets_kg = 0.0_f32;
+        let mut total_input_mass_kg = 0.0_f32;
+
+        for stream in streams {
+            let mass = stream.mass_non_negative();
+            total_input_mass_kg += mass;
+
+            if !self.quality.accepts(stream) {
+                losses.quality_rejected_kg += mass;
+                continue;
+            }
+            let profile = match self.resin_profile(stream.plastic_type) {
+                Some(p) if p.suitable_for_device_shells => p,
+                _ => {
+                    losses.unsuitable_resin_kg += mass;
+                    continue;
+                }
+            };
+
+            let after_sorting = mass * profile.sorting_yield.clamp(0.0, 1.0);
+            let after_washing = after_sorting * profile.washing_yield.clamp(0.0, 1.0);
+            let after_pelletizing = after_washing * profile.pelletizing_yield.clamp(0.0, 1.0);
+
+            losses.sorting_kg += mass - after_sorting;
+            losses.washing_kg += after_sorting - after_washing;
+            losses.pelletizing_kg += after_washing - after_pelletizing;
+            recycled_pellets_kg += after_pelletizing;
+        }
+
+        let virgin_input_mass_kg = virgin_input_kg.max(0.0);
+        let shell_feedstock_mass_kg = recycled_pellets_kg + virgin_input_mass_kg;
+        let recycled_content_ratio = if shell_feedstock_mass_kg > 0.0 {
+            recycled_pellets_kg / shell_feedstock_mass_kg
+        } else {
+            0.0
+        };
+
+        let kg_per_device_shell = kg_per_device_shell.max(0.001); // avoid division by zero
+        let estimated_device_count = (shell_feedstock_mass_kg / kg_per_device_shell)
+            .floor()
+            .max(0.0) as u32;
+
+        DevicePlasticBatch {
+            batch_id,
+            total_input_mass_kg,
+            estimated_device_count,
+            recycled_content_ratio,
+            shell_feedstock_mass_kg,
+            virgin_input_mass_kg,
+            stage_losses: losses,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+/// Nicotine products tracked by the program. Each product is counted in its
+/// natural retail unit (see `unit_label`), so consumption stays comparable
+/// with what people actually self-report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NicotineProduct {
+    /// Combustible cigarettes, counted in packs.
+    Cigarette,
+    /// Cigars and cigarillos, counted individually.
+    Cigar,
+    /// Single-use disposable vapes, counted per device.
+    DisposableVape,
+    /// Refillable pod systems, counted per pod.
+    PodVape,
+    /// Heated tobacco sticks, counted in packs.
+    HeatedTobacco,
+}
+
+impl NicotineProduct {
+    pub fn unit_label(&self) -> &'static str {
+        match self {
+            NicotineProduct::Cigarette => "pack",
+            NicotineProduct::Cigar => "cigar",
+            NicotineProduct::DisposableVape => "device",
+            NicotineProduct::PodVape => "pod",
+            NicotineProduct::HeatedTobacco => "pack",
+        }
+    }
+
+    /// True for products whose use produces combustion smoke and butts.
+    pub fn is_combustible(&self) -> bool {
+        matches!(self, NicotineProduct::Cigarette | NicotineProduct::Cigar)
+    }
+}
+
+/// Self-reported daily use of one product.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductUsage {
+    pub product: NicotineProduct,
+    /// Average units per day in the product's `unit_label` (can be fractional).
+    pub units_per_day: f32,
+}
+
+/// Nicotine-equivalence factors expressing one unit of each product as
+/// packs of cigarettes. Cigarettes are the reference (1.0) and are not
+/// configurable. Values must come from the program's clinical references;
+/// there is deliberately no default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NicotineEquivalence {
+    pub packs_per_cigar: f32,
+    pub packs_per_disposable_vape: f32,
+    pub packs_per_vape_pod: f32,
+    pub packs_per_heated_tobacco_pack: f32,
+}
+
+impl NicotineEquivalence {
+    /// Pack-equivalents for one unit of `product`.
+    pub fn packs_per_unit(&self, product: NicotineProduct) -> f32 {
+        let factor = match product {
+            NicotineProduct::Cigarette => 1.0,
+            NicotineProduct::Cigar => self.packs_per_cigar,
+            NicotineProduct::DisposableVape => self.packs_per_disposable_vape,
+            NicotineProduct::PodVape => self.packs_per_vape_pod,
+            NicotineProduct::HeatedTobacco => self.packs_per_heated_tobacco_pack,
+        };
+        factor.max(0.0)
+    }
+
+    /// Total daily consumption across products, normalized to packs/day.
+    pub fn packs_equivalent_per_day(&self, usage: &[ProductUsage]) -> f32 {
+        usage
+            .iter()
+            .map(|u| u.units_per_day.max(0.0) * self.packs_per_unit(u.product))
+            .sum()
+    }
+}
+
+/// Per-unit footprint of a non-cigarette product. Cigarettes keep using the
+/// per-cigarette fields on `CarbonFactors`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductFootprintFactor {
+    pub product: NicotineProduct,
+    /// CO₂e per unit from manufacture and use (kg).
+    pub kg_co2e_per_unit: f32,
+    /// CO₂e per unit from disposal, e.g. butts, pods or battery waste (kg).
+    pub kg_co2e_waste_per_unit: f32,
+}
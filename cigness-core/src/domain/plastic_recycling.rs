@@ -2,12 +2,32 @@ use serde::{Deserialize, Serialize};
 
 /// Accepted plastic streams for Cigness device manufacturing.
 /// These map directly to real-world resin codes and waste streams.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PlasticType {
     PET,
     HDPE,
     PP,
     ABS,
+    /// Low-density polyethylene films, accepted at C-nodes.
+    LDPE,
+    /// Mixed small plastics of unknown or blended resin.
+    MixedPlastics,
+}
+
+impl PlasticType {
+    /// Parse a resin code as written in the node and plastic-loop configs
+    /// (e.g. "PET", "LDPE", "mixed").
+    pub fn from_resin_code(code: &str) -> Option<PlasticType> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "PET" => Some(PlasticType::PET),
+            "HDPE" => Some(PlasticType::HDPE),
+            "PP" => Some(PlasticType::PP),
+            "ABS" => Some(PlasticType::ABS),
+            "LDPE" => Some(PlasticType::LDPE),
+            "MIXED" => Some(PlasticType::MixedPlastics),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,10 +38,23 @@ pub struct PlasticStream {
     pub plastic_type: PlasticType,
     /// Mass of recovered plastic in kilograms.
     pub mass_kg: f32,
+    /// Product category the plastic came from (e.g., "bottles", "films").
+    #[serde(default)]
+    pub source_category: Option<String>,
+    /// Stream type of the source (e.g., "MUNICIPAL-MRF", "CIGNESS-RNODE").
+    #[serde(default)]
+    pub stream_type: Option<String>,
+    /// Measured contaminant share (percent of mass), if tested at intake.
+    #[serde(default)]
+    pub contaminant_percent: Option<f32>,
+    /// Measured moisture (percent of mass), if tested at intake.
+    #[serde(default)]
+    pub moisture_percent: Option<f32>,
 }
 
 /// Batch of recycled plastic allocated to device manufacturing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "DevicePlasticBatchRecord")]
 pub struct DevicePlasticBatch {
     /// Unique batch identifier for traceability.
     pub batch_id: String,
@@ -31,6 +64,66 @@ pub struct DevicePlasticBatch {
     pub estimated_device_count: u32,
     /// Calculated recycled-content percentage (0.0–1.0).
     pub recycled_content_ratio: f32,
+    /// Blended pellet mass available for device shells (kg).
+    pub shell_feedstock_mass_kg: f32,
+    /// Virgin resin blended into the feedstock (kg).
+    pub virgin_input_mass_kg: f32,
+    /// Mass lost or diverted at each processing stage.
+    pub stage_losses: StageMassLosses,
+}
+
+/// Stored form of `DevicePlasticBatch`. Batches saved before the material
+/// flow model have no feedstock field; their whole input went to shells.
+#[derive(Debug, Deserialize)]
+struct DevicePlasticBatchRecord {
+    batch_id: String,
+    total_input_mass_kg: f32,
+    estimated_device_count: u32,
+    recycled_content_ratio: f32,
+    #[serde(default)]
+    shell_feedstock_mass_kg: Option<f32>,
+    #[serde(default)]
+    virgin_input_mass_kg: f32,
+    #[serde(default)]
+    stage_losses: StageMassLosses,
+}
+
+impl From<DevicePlasticBatchRecord> for DevicePlasticBatch {
+    fn from(record: DevicePlasticBatchRecord) -> Self {
+        DevicePlasticBatch {
+            shell_feedstock_mass_kg: record
+                .shell_feedstock_mass_kg
+                .unwrap_or(record.total_input_mass_kg),
+            batch_id: record.batch_id,
+            total_input_mass_kg: record.total_input_mass_kg,
+            estimated_device_count: record.estimated_device_count,
+            recycled_content_ratio: record.recycled_content_ratio,
+            virgin_input_mass_kg: record.virgin_input_mass_kg,
+            stage_losses: record.stage_losses,
+        }
+    }
+}
+
+/// Mass removed from a batch at each stage, in kilograms.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StageMassLosses {
+    /// Streams rejected for exceeding contamination or moisture limits.
+    pub quality_rejected_kg: f32,
+    /// Resin that is not suitable for device shells.
+    pub unsuitable_resin_kg: f32,
+    pub sorting_kg: f32,
+    pub washing_kg: f32,
+    pub pelletizing_kg: f32,
+}
+
+impl StageMassLosses {
+    pub fn total_kg(&self) -> f32 {
+        self.quality_rejected_kg
+            + self.unsuitable_resin_kg
+            + self.sorting_kg
+            + self.washing_kg
+            + self.pelletizing_kg
+    }
 }
 
 impl PlasticStream {
@@ -40,8 +133,9 @@ impl PlasticStream {
 }
 
 impl DevicePlasticBatch {
-    /// Create a new batch assuming 100% recycled plastic input and a
-    /// target plastic mass per device (kg).
+    /// Create a new batch assuming 100% recycled plastic input, no process
+    /// losses and a target plastic mass per device (kg). Use
+    /// `MaterialFlowModel::build_batch` for a yield-aware batch.
     pub fn from_streams(
         batch_id: String,
         streams: &[PlasticStream],
@@ -58,6 +152,35 @@ impl DevicePlasticBatch {
             total_input_mass_kg,
             estimated_device_count,
             recycled_content_ratio: 1.0,
+            shell_feedstock_mass_kg: total_input_mass_kg,
+            virgin_input_mass_kg: 0.0,
+            stage_losses: StageMassLosses::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_without_feedstock_field_uses_input_mass() {
+        let batch: DevicePlasticBatch = serde_json::from_str(
+            r#"{"batch_id": "B1", "total_input_mass_kg": 12.5,
+                "estimated_device_count": 50, "recycled_content_ratio": 1.0}"#,
+        )
+        .unwrap();
+        assert_eq!(batch.shell_feedstock_mass_kg, 12.5);
+        assert_eq!(batch.virgin_input_mass_kg, 0.0);
+    }
+
+    #[test]
+    fn test_batch_round_trips_feedstock_mass() {
+        let mut batch = DevicePlasticBatch::from_streams("B2".to_string(), &[], 0.25);
+        batch.total_input_mass_kg = 10.0;
+        batch.shell_feedstock_mass_kg = 8.0;
+        let json = serde_json::to_string(&batch).unwrap();
+        let back: DevicePlasticBatch = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.shell_feedstock_mass_kg, 8.0);
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::nicotine_product::ProductUsage;
+use crate::domain::smoker_profile::SmokerProfile;
+use crate::services::cessation_plan::CessationPlan;
+
+/// Horizon over which `CessationPlan::target_packs_per_day_90d` applies.
+const PLAN_HORIZON_DAYS: f32 = 90.0;
+
+/// One timestamped revision of a smoker's self-reported consumption.
+/// Field layout mirrors the `smoker_journeys` source in
+/// ops/data-lake-ingest.cigness.yaml.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmokerProfileRevision {
+    pub profile_id: String,
+    pub packs_per_day: f32,
+    pub years_smoked: f32,
+    pub region_code: String,
+    /// Daily use of non-cigarette products; empty in revisions recorded
+    /// before other products were tracked.
+    #[serde(default)]
+    pub other_products: Vec<ProductUsage>,
+    /// The last time the user smoked, or None if not reported.
+    pub last_smoked_at_utc: Option<DateTime<Utc>>,
+    /// When this revision was recorded.
+    pub updated_at_utc: DateTime<Utc>,
+}
+
+impl SmokerProfileRevision {
+    /// Snapshot the consumption fields of a profile at a point in time.
+    pub fn from_profile(
+        profile: &SmokerProfile,
+        last_smoked_at_utc: Option<DateTime<Utc>>,
+        updated_at_utc: DateTime<Utc>,
+    ) -> Self {
+        SmokerProfileRevision {
+            profile_id: profile.profile_id.clone(),
+            packs_per_day: profile.packs_per_day.max(0.0),
+            years_smoked: profile.years_smoked.max(0.0),
+            region_code: profile.region_code.clone(),
+            other_products: profile
+                .other_products
+                .iter()
+                .map(|u| ProductUsage {
+                    product: u.product,
+                    units_per_day: u.units_per_day.max(0.0),
+                })
+                .collect(),
+            last_smoked_at_utc,
+            updated_at_utc,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JourneyError {
+    #[error("Revision belongs to profile {found}, expected {expected}")]
+    ProfileMismatch { expected: String, found: String },
+}
+
+/// Longitudinal history of a single enrolled person, ordered by
+/// `updated_at_utc`. The first revision is treated as the enrollment baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmokerJourney {
+    pub profile_id: String,
+    revisions: Vec<SmokerProfileRevision>,
+}
+
+impl SmokerJourney {
+    pub fn new(profile_id: String) -> Self {
+        SmokerJourney {
+            profile_id,
+            revisions: Vec::new(),
+        }
+    }
+
+    /// Build a journey from revisions in any order. All revisions must
+    /// belong to `profile_id`.
+    pub fn from_revisions(
+        profile_id: String,
+        revisions: Vec<SmokerProfileRevision>,
+    ) -> Result<Self, JourneyError> {
+        let mut journey = SmokerJourney::new(profile_id);
+        for rev in revisions {
+            journey.record(rev)?;
+        }
+        Ok(journey)
+    }
+
+    /// Insert a revision, keeping the history sorted by timestamp.
+    pub fn record(&mut self, revision: SmokerProfileRevision) -> Result<(), JourneyError> {
+        if revision.profile_id != self.profile_id {
+            return Err(JourneyError::ProfileMismatch {
+                expected: self.profile_id.clone(),
+                found: revision.profile_id,
+            });
+        }
+        let idx = self
+            .revisions
+            .partition_point(|r| r.updated_at_utc <= revision.updated_at_utc);
+        self.revisions.insert(idx, revision);
+        Ok(())
+    }
+
+    pub fn revisions(&self) -> &[SmokerProfileRevision] {
+        &self.revisions
+    }
+
+    pub fn baseline(&self) -> Option<&SmokerProfileRevision> {
+        self.revisions.first()
+    }
+
+    pub fn latest(&self) -> Option<&SmokerProfileRevision> {
+        self.revisions.last()
+    }
+
+    /// Average reduction in packs/day per week between baseline and the
+    /// latest revision. Positive values mean consumption is going down.
+    /// Returns None until two revisions at distinct times exist.
+    pub fn reduction_rate_packs_per_week(&self) -> Option<f32> {
+        let (first, last) = (self.baseline()?, self.latest()?);
+        let elapsed_days = days_between(first.updated_at_utc, last.updated_at_utc);
+        if elapsed_days <= 0.0 {
+            return None;
+        }
+        let delta = first.packs_per_day.max(0.0) - last.packs_per_day.max(0.0);
+        Some(delta / elapsed_days * 7.0)
+    }
+
+    /// Time from baseline until the first revision at or below half the
+    /// baseline packs/day, or None if that has not happened yet.
+    pub fn time_to_half_reduction(&self) -> Option<Duration> {
+        let first = self.baseline()?;
+        let baseline_ppd = first.packs_per_day.max(0.0);
+        if baseline_ppd <= 0.0 {
+            return None;
+        }
+        self.revisions
+            .iter()
+            .skip(1)
+            .find(|r| r.packs_per_day.max(0.0) <= baseline_ppd * 0.5)
+            .map(|r| r.updated_at_utc - first.updated_at_utc)
+    }
+
+    /// Expected packs/day at `at` on a linear path from the baseline to the
+    /// plan's 90-day target, holding at the target after day 90.
+    pub fn expected_packs_per_day_at(
+        &self,
+        plan: &CessationPlan,
+        at: DateTime<Utc>,
+    ) -> Option<f32> {
+        let first = self.baseline()?;
+        let baseline_ppd = first.packs_per_day.max(0.0);
+        let target_ppd = plan.target_packs_per_day_90d.max(0.0).min(baseline_ppd);
+        let progress =
+            (days_between(first.updated_at_utc, at) / PLAN_HORIZON_DAYS).clamp(0.0, 1.0);
+        Some(baseline_ppd - (baseline_ppd - target_ppd) * progress)
+    }
+
+    /// True if the latest revision is at or below the expected packs/day
+    /// for its timestamp. Returns false for a plan belonging to another
+    /// profile or an empty journey.
+    pub fn is_on_track(&self, plan: &CessationPlan) -> bool {
+        if plan.profile_id != self.profile_id {
+            return false;
+        }
+        let latest = match self.latest() {
+            Some(l) => l,
+            None => return false,
+        };
+        match self.expected_packs_per_day_at(plan, latest.updated_at_utc) {
+            Some(expected) => latest.packs_per_day.max(0.0) <= expected + f32::EPSILON,
+            None => false,
+        }
+    }
+}
+
+fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f32 {
+    (to - from).num_seconds() as f32 / 86_400.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::nicotine_product::NicotineProduct;
+
+    #[test]
+    fn test_revision_keeps_other_products() {
+        let profile = SmokerProfile::builder("p-1")
+            .age_years(40)
+            .packs_per_day(0.5)
+            .years_smoked(20.0)
+            .region_code("US-AZ")
+            .product(NicotineProduct::PodVape, 2.0)
+            .build_adult()
+            .unwrap();
+        let revision = SmokerProfileRevision::from_profile(&profile, None, Utc::now());
+        assert_eq!(revision.other_products.len(), 1);
+        assert_eq!(revision.other_products[0].product, NicotineProduct::PodVape);
+        assert_eq!(revision.other_products[0].units_per_day, 2.0);
+    }
+
+    #[test]
+    fn test_revision_without_other_products_deserializes() {
+        let revision: SmokerProfileRevision = serde_json::from_str(
+            r#"{"profile_id": "p-1", "packs_per_day": 1.0, "years_smoked": 10.0,
+                "region_code": "US-AZ", "last_smoked_at_utc": null,
+                "updated_at_utc": "2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert!(revision.other_products.is_empty());
+    }
+}
@@ -1,4 +1,15 @@
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::nicotine_product::{NicotineEquivalence, NicotineProduct, ProductUsage};
+
+/// Age bounds from the `modes` section of r_node_xr_profile.cigness.json.
+pub const YOUTH_MIN_AGE: u8 = 12;
+pub const ADULT_MIN_AGE: u8 = 18;
+/// Upper bound accepted for self-reported packs per day.
+const MAX_PACKS_PER_DAY: f32 = 10.0;
+/// Upper bound accepted for self-reported daily units of any other product.
+const MAX_OTHER_PRODUCT_UNITS_PER_DAY: f32 = 100.0;
 
 /// SmokerProfile captures real, self-reported consumption and context data
 /// for non-fictional individuals enrolled in the Cigness program.
@@ -21,6 +32,10 @@ pub struct SmokerProfile {
     pub clinician_recommended_cessation: bool,
     /// Country or region code for regulatory and emissions models (e.g., "US-AZ").
     pub region_code: String,
+    /// Daily use of products other than cigarettes (vapes, cigars, heated
+    /// tobacco). Cigarette use stays in `packs_per_day`.
+    #[serde(default)]
+    pub other_products: Vec<ProductUsage>,
 }
 
 /// Derived metrics to support health and carbon calculations without
@@ -43,9 +58,35 @@ pub enum HealthRiskBand {
 
 impl SmokerProfile {
     /// Compute derived metrics from a real profile, enforcing non-negative
-    /// values and clamping to reasonable bounds.
+    /// values and clamping to reasonable bounds. Only cigarettes count
+    /// toward pack-years; see `derive_metrics_with` for other products.
     pub fn derive_metrics(&self) -> SmokerDerivedMetrics {
-        let packs_per_day = self.packs_per_day.max(0.0);
+        self.metrics_for_packs_per_day(self.packs_per_day)
+    }
+
+    /// Like `derive_metrics`, but pack-years use the nicotine-equivalent
+    /// packs/day across all products.
+    pub fn derive_metrics_with(&self, equivalence: &NicotineEquivalence) -> SmokerDerivedMetrics {
+        self.metrics_for_packs_per_day(self.packs_equivalent_per_day(equivalence))
+    }
+
+    /// All products in use, with cigarettes expressed in packs/day.
+    pub fn product_usage(&self) -> Vec<ProductUsage> {
+        let mut usage = vec![ProductUsage {
+            product: NicotineProduct::Cigarette,
+            units_per_day: self.packs_per_day.max(0.0),
+        }];
+        usage.extend(self.other_products.iter().cloned());
+        usage
+    }
+
+    /// Nicotine-equivalent packs/day across all products.
+    pub fn packs_equivalent_per_day(&self, equivalence: &NicotineEquivalence) -> f32 {
+        equivalence.packs_equivalent_per_day(&self.product_usage())
+    }
+
+    fn metrics_for_packs_per_day(&self, packs_per_day: f32) -> SmokerDerivedMetrics {
+        let packs_per_day = packs_per_day.max(0.0);
         let years_smoked = self.years_smoked.max(0.0);
         let pack_years = packs_per_day * years_smoked;
 
@@ -65,3 +106,228 @@ impl SmokerProfile {
         }
     }
 }
+
+/// Program routing by age, matching the youth/adult modes of the R-node
+/// XR profile. Youth profiles never enter the adult cessation flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AgeMode {
+    Youth,
+    Adult,
+}
+
+impl AgeMode {
+    /// Resolve the mode for an age, or None if below the youth minimum.
+    pub fn for_age(age_years: u8) -> Option<AgeMode> {
+        if age_years >= ADULT_MIN_AGE {
+            Some(AgeMode::Adult)
+        } else if age_years >= YOUTH_MIN_AGE {
+            Some(AgeMode::Youth)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ProfileError {
+    #[error("Missing required field: {0}")]
+    MissingField(&'static str),
+    #[error("Value out of allowed range: {field} = {value}")]
+    OutOfRange { field: &'static str, value: f32 },
+    #[error("Age {0} is below the program minimum of {}", YOUTH_MIN_AGE)]
+    BelowMinimumAge(u8),
+    #[error("Age {0} is a youth profile and must be routed to the youth flow")]
+    YouthProfile(u8),
+    #[error("years_smoked ({years_smoked}) exceeds age ({age_years})")]
+    YearsSmokedExceedAge { years_smoked: f32, age_years: u8 },
+    #[error("Cigarette use belongs in packs_per_day, not other_products")]
+    CigaretteInOtherProducts,
+}
+
+impl SmokerProfile {
+    pub fn builder(profile_id: impl Into<String>) -> SmokerProfileBuilder {
+        SmokerProfileBuilder::new(profile_id)
+    }
+
+    /// Age mode for routing, or None if the age is below the program minimum.
+    pub fn age_mode(&self) -> Option<AgeMode> {
+        AgeMode::for_age(self.age_years)
+    }
+
+    /// Check an existing profile (e.g., deserialized from a shard) against
+    /// the same rules the builder enforces.
+    pub fn validate(&self) -> Result<AgeMode, ProfileError> {
+        if self.profile_id.trim().is_empty() {
+            return Err(ProfileError::MissingField("profile_id"));
+        }
+        if self.region_code.trim().is_empty() {
+            return Err(ProfileError::MissingField("region_code"));
+        }
+        if !self.packs_per_day.is_finite()
+            || !(0.0..=MAX_PACKS_PER_DAY).contains(&self.packs_per_day)
+        {
+            return Err(ProfileError::OutOfRange {
+                field: "packs_per_day",
+                value: self.packs_per_day,
+            });
+        }
+        for usage in &self.other_products {
+            if usage.product == NicotineProduct::Cigarette {
+                return Err(ProfileError::CigaretteInOtherProducts);
+            }
+            if !usage.units_per_day.is_finite()
+                || !(0.0..=MAX_OTHER_PRODUCT_UNITS_PER_DAY).contains(&usage.units_per_day)
+            {
+                return Err(ProfileError::OutOfRange {
+                    field: "other_products.units_per_day",
+                    value: usage.units_per_day,
+                });
+            }
+        }
+        if !self.years_smoked.is_finite() || self.years_smoked < 0.0 {
+            return Err(ProfileError::OutOfRange {
+                field: "years_smoked",
+                value: self.years_smoked,
+            });
+        }
+        if self.years_smoked > self.age_years as f32 {
+            return Err(ProfileError::YearsSmokedExceedAge {
+                years_smoked: self.years_smoked,
+                age_years: self.age_years,
+            });
+        }
+        self.age_mode()
+            .ok_or(ProfileError::BelowMinimumAge(self.age_years))
+    }
+}
+
+/// Validating builder for `SmokerProfile`. Unlike `derive_metrics`, it never
+/// clamps: any invalid input is reported as a `ProfileError`.
+#[derive(Debug, Clone, Default)]
+pub struct SmokerProfileBuilder {
+    profile_id: String,
+    age_years: Option<u8>,
+    packs_per_day: Option<f32>,
+    years_smoked: Option<f32>,
+    has_diagnosed_condition: bool,
+    reports_severe_anxiety: bool,
+    clinician_recommended_cessation: bool,
+    region_code: Option<String>,
+    other_products: Vec<ProductUsage>,
+}
+
+impl SmokerProfileBuilder {
+    pub fn new(profile_id: impl Into<String>) -> Self {
+        SmokerProfileBuilder {
+            profile_id: profile_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn age_years(mut self, age_years: u8) -> Self {
+        self.age_years = Some(age_years);
+        self
+    }
+
+    pub fn packs_per_day(mut self, packs_per_day: f32) -> Self {
+        self.packs_per_day = Some(packs_per_day);
+        self
+    }
+
+    pub fn years_smoked(mut self, years_smoked: f32) -> Self {
+        self.years_smoked = Some(years_smoked);
+        self
+    }
+
+    pub fn diagnosed_condition(mut self, value: bool) -> Self {
+        self.has_diagnosed_condition = value;
+        self
+    }
+
+    pub fn severe_anxiety(mut self, value: bool) -> Self {
+        self.reports_severe_anxiety = value;
+        self
+    }
+
+    pub fn clinician_recommended_cessation(mut self, value: bool) -> Self {
+        self.clinician_recommended_cessation = value;
+        self
+    }
+
+    pub fn region_code(mut self, region_code: impl Into<String>) -> Self {
+        self.region_code = Some(region_code.into());
+        self
+    }
+
+    /// Record daily use of a non-cigarette product.
+    pub fn product(mut self, product: NicotineProduct, units_per_day: f32) -> Self {
+        self.other_products.push(ProductUsage {
+            product,
+            units_per_day,
+        });
+        self
+    }
+
+    /// Build a profile of any eligible age mode.
+    pub fn build(self) -> Result<SmokerProfile, ProfileError> {
+        let profile = SmokerProfile {
+            profile_id: self.profile_id,
+            age_years: self.age_years.ok_or(ProfileError::MissingField("age_years"))?,
+            packs_per_day: self
+                .packs_per_day
+                .ok_or(ProfileError::MissingField("packs_per_day"))?,
+            years_smoked: self
+                .years_smoked
+                .ok_or(ProfileError::MissingField("years_smoked"))?,
+            has_diagnosed_condition: self.has_diagnosed_condition,
+            reports_severe_anxiety: self.reports_severe_anxiety,
+            clinician_recommended_cessation: self.clinician_recommended_cessation,
+            region_code: self
+                .region_code
+                .ok_or(ProfileError::MissingField("region_code"))?,
+            other_products: self.other_products,
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Build a profile for the adult cessation flow, rejecting youth profiles.
+    pub fn build_adult(self) -> Result<SmokerProfile, ProfileError> {
+        let profile = self.build()?;
+        match profile.age_mode() {
+            Some(AgeMode::Adult) => Ok(profile),
+            _ => Err(ProfileError::YouthProfile(profile.age_years)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> SmokerProfileBuilder {
+        SmokerProfile::builder("p-1")
+            .age_years(40)
+            .packs_per_day(0.5)
+            .years_smoked(20.0)
+            .region_code("US-AZ")
+    }
+
+    #[test]
+    fn test_other_product_units_are_bounded() {
+        assert!(builder()
+            .product(NicotineProduct::DisposableVape, 1.0)
+            .build()
+            .is_ok());
+        assert_eq!(
+            builder()
+                .product(NicotineProduct::DisposableVape, 500.0)
+                .build()
+                .unwrap_err(),
+            ProfileError::OutOfRange {
+                field: "other_products.units_per_day",
+                value: 500.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::carbon_model::CarbonFactors;
+use crate::domain::smoker_profile::SmokerProfile;
+
+/// Region key used when no more specific factors exist.
+pub const GLOBAL_DEFAULT_REGION: &str = "global_default";
+
+/// Relative difference above which two sources are considered to disagree.
+const CONFLICT_TOLERANCE: f32 = 0.01;
+
+/// Wire format of the `eco_impact_model` section in cigness.plastic-loop.json.
+#[derive(Debug, Deserialize)]
+struct PlasticLoopRoot {
+    id: String,
+    eco_impact_model: EcoImpactModel,
+}
+
+#[derive(Debug, Deserialize)]
+struct EcoImpactModel {
+    kg_co2e_per_cigarette: f32,
+    kg_co2e_per_butt_waste: f32,
+    cigarettes_per_pack: u32,
+}
+
+/// Wire format of qpudatashards/tobacco_footprint_factors_v1.csv.
+#[derive(Debug, Deserialize)]
+struct FootprintFactorRow {
+    region: String,
+    co2_kg_per_cig: f32,
+    water_l_per_cig: f32,
+    butt_kg_per_cig: f32,
+    source_ref: String,
+}
+
+#[derive(Debug, Error)]
+pub enum FactorRegistryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("CSV parse error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Value out of allowed range: {0}")]
+    Range(String),
+}
+
+/// Where a factor set came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FactorSource {
+    PlasticLoopConfig,
+    FootprintCsv,
+}
+
+/// Carbon factors for one region, tagged with their origin and version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedCarbonFactors {
+    pub region: String,
+    /// Version tag, e.g. the config id or CSV file stem.
+    pub version: String,
+    pub source: FactorSource,
+    /// Free-text citation for the underlying data, if provided.
+    pub source_ref: Option<String>,
+    pub factors: CarbonFactors,
+    /// Lifecycle water per cigarette (L), when the source provides it.
+    pub l_water_per_cigarette: Option<f32>,
+    /// Butt mass per cigarette (kg), when the source provides it.
+    pub kg_butt_per_cigarette: Option<f32>,
+}
+
+/// Disagreement between sources that was resolved during loading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactorConflictWarning {
+    pub region: String,
+    pub field: String,
+    pub kept_value: f32,
+    pub kept_version: String,
+    pub discarded_value: f32,
+    pub discarded_version: String,
+}
+
+/// Registry of regional carbon factors merged from the plastic-loop config
+/// and the footprint CSV.
+///
+/// The plastic-loop config is operator-controlled and wins any conflict on
+/// the global default; it also supplies butt-waste CO₂e and pack size, which
+/// the CSV does not carry.
+///
+/// CSV rows are keyed by local area names (e.g. "phoenix_urban") rather
+/// than region codes, so they are only reachable from a profile's
+/// `region_code` through `region_aliases`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonFactorRegistry {
+    entries: HashMap<String, VersionedCarbonFactors>,
+    /// Region code (e.g. "US-AZ-MARICOPA-PHX") to CSV region.
+    region_aliases: HashMap<String, String>,
+    warnings: Vec<FactorConflictWarning>,
+}
+
+impl CarbonFactorRegistry {
+    pub fn load_from_files<P: AsRef<Path>>(
+        plastic_loop_path: P,
+        footprint_csv_path: P,
+        region_aliases: HashMap<String, String>,
+    ) -> Result<Self, FactorRegistryError> {
+        let mut buf = String::new();
+        File::open(plastic_loop_path.as_ref())?.read_to_string(&mut buf)?;
+        let plastic_root: PlasticLoopRoot = serde_json::from_str(&buf)?;
+
+        let csv_version = footprint_csv_path
+            .as_ref()
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "footprint_csv".to_string());
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(File::open(footprint_csv_path.as_ref())?);
+        let mut rows = Vec::new();
+        for result in rdr.deserialize::<FootprintFactorRow>() {
+            rows.push(result?);
+        }
+
+        Self::from_sources(plastic_root, rows, &csv_version, region_aliases)
+    }
+
+    fn from_sources(
+        plastic_root: PlasticLoopRoot,
+        rows: Vec<FootprintFactorRow>,
+        csv_version: &str,
+        region_aliases: HashMap<String, String>,
+    ) -> Result<Self, FactorRegistryError> {
+        let eco = &plastic_root.eco_impact_model;
+        if eco.kg_co2e_per_cigarette < 0.0 || eco.kg_co2e_per_butt_waste < 0.0 {
+            return Err(FactorRegistryError::Range(
+                "eco_impact_model factors must be non-negative".into(),
+            ));
+        }
+
+        let mut entries = HashMap::new();
+        let mut warnings = Vec::new();
+
+        for row in rows {
+            if row.co2_kg_per_cig < 0.0 || row.water_l_per_cig < 0.0 || row.butt_kg_per_cig < 0.0 {
+                return Err(FactorRegistryError::Range(format!(
+                    "negative factor for region {}",
+                    row.region
+                )));
+            }
+            entries.insert(
+                region_key(&row.region),
+                VersionedCarbonFactors {
+                    region: row.region,
+                    version: csv_version.to_string(),
+                    source: FactorSource::FootprintCsv,
+                    source_ref: Some(row.source_ref),
+                    factors: CarbonFactors {
+                        kg_co2e_per_cigarette: row.co2_kg_per_cig,
+                        cigarettes_per_pack: eco.cigarettes_per_pack,
+                        kg_co2e_per_butt_waste: eco.kg_co2e_per_butt_waste,
+                        product_factors: Vec::new(),
+                    },
+                    l_water_per_cigarette: Some(row.water_l_per_cig),
+                    kg_butt_per_cigarette: Some(row.butt_kg_per_cig),
+                },
+            );
+        }
+
+        let config_factors = CarbonFactors {
+            kg_co2e_per_cigarette: eco.kg_co2e_per_cigarette,
+            cigarettes_per_pack: eco.cigarettes_per_pack,
+            kg_co2e_per_butt_waste: eco.kg_co2e_per_butt_waste,
+            product_factors: Vec::new(),
+        };
+
+        let global_key = region_key(GLOBAL_DEFAULT_REGION);
+        let global = match entries.remove(&global_key) {
+            Some(csv_entry) => {
+                let csv_value = csv_entry.factors.kg_co2e_per_cigarette;
+                if differs(csv_value, eco.kg_co2e_per_cigarette) {
+                    warnings.push(FactorConflictWarning {
+                        region: GLOBAL_DEFAULT_REGION.to_string(),
+                        field: "kg_co2e_per_cigarette".to_string(),
+                        kept_value: eco.kg_co2e_per_cigarette,
+                        kept_version: plastic_root.id.clone(),
+                        discarded_value: csv_value,
+                        discarded_version: csv_entry.version.clone(),
+                    });
+                }
+                VersionedCarbonFactors {
+                    region: GLOBAL_DEFAULT_REGION.to_string(),
+                    version: plastic_root.id.clone(),
+                    source: FactorSource::PlasticLoopConfig,
+                    factors: config_factors,
+                    ..csv_entry
+                }
+            }
+            None => VersionedCarbonFactors {
+                region: GLOBAL_DEFAULT_REGION.to_string(),
+                version: plastic_root.id.clone(),
+                source: FactorSource::PlasticLoopConfig,
+                source_ref: None,
+                factors: config_factors,
+                l_water_per_cigarette: None,
+                kg_butt_per_cigarette: None,
+            },
+        };
+        entries.insert(global_key, global);
+
+        let mut aliases = HashMap::new();
+        for (code, region) in region_aliases {
+            let target = region_key(&region);
+            if !entries.contains_key(&target) {
+                return Err(FactorRegistryError::Range(format!(
+                    "region code {} maps to unknown region {}",
+                    code, region
+                )));
+            }
+            aliases.insert(region_key(&code), target);
+        }
+
+        Ok(CarbonFactorRegistry {
+            entries,
+            region_aliases: aliases,
+            warnings,
+        })
+    }
+
+    /// Conflicts detected while merging sources.
+    pub fn warnings(&self) -> &[FactorConflictWarning] {
+        &self.warnings
+    }
+
+    /// Exact lookup without fallback.
+    pub fn get(&self, region: &str) -> Option<&VersionedCarbonFactors> {
+        self.entries.get(&region_key(region))
+    }
+
+    /// Resolve factors for a region code, walking the fallback chain
+    /// (e.g. "US-AZ" → "US" → global_default). At each step an exact entry
+    /// wins over an alias.
+    pub fn resolve(&self, region_code: &str) -> Option<&VersionedCarbonFactors> {
+        Self::fallback_chain(region_code).iter().find_map(|r| {
+            self.get(r).or_else(|| {
+                self.region_aliases
+                    .get(&region_key(r))
+                    .and_then(|target| self.entries.get(target))
+            })
+        })
+    }
+
+    pub fn resolve_for_profile(&self, profile: &SmokerProfile) -> Option<&VersionedCarbonFactors> {
+        self.resolve(&profile.region_code)
+    }
+
+    /// Region codes tried in order for `region_code`, most specific first.
+    pub fn fallback_chain(region_code: &str) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut code = region_code.trim();
+        while !code.is_empty() {
+            chain.push(code.to_string());
+            code = match code.rfind('-') {
+                Some(idx) => &code[..idx],
+                None => "",
+            };
+        }
+        chain.push(GLOBAL_DEFAULT_REGION.to_string());
+        chain
+    }
+}
+
+fn region_key(region: &str) -> String {
+    region.trim().to_ascii_lowercase()
+}
+
+fn differs(a: f32, b: f32) -> bool {
+    let scale = a.abs().max(b.abs()).max(f32::EPSILON);
+    (a - b).abs() / scale > CONFLICT_TOLERANCE
+}
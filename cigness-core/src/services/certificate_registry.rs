@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::services::forest_protection::{PackagingMaterial, PackagingShipment};
+
+/// Kind of recycled content a certificate attests to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecycledContentType {
+    /// Post-consumer recycled.
+    Pcr,
+    /// Post-industrial recycled.
+    Pir,
+}
+
+impl RecycledContentType {
+    /// PCR and PIR content can only back recycled packaging materials.
+    pub fn covers(&self, material: PackagingMaterial) -> bool {
+        material.is_recycled()
+    }
+}
+
+/// A recycled-content certificate as held in the local certificate store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecycledContentCertificate {
+    pub certificate_id: String,
+    pub issuer: String,
+    /// Sawmill the certificate was issued to.
+    pub holder_id: String,
+    pub content_type: RecycledContentType,
+    /// Packaging material the certificate covers.
+    pub material: PackagingMaterial,
+    pub valid_from: NaiveDate,
+    /// Last day the certificate is valid, inclusive.
+    pub valid_until: NaiveDate,
+    pub covered_mass_kg: f32,
+}
+
+impl RecycledContentCertificate {
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        date >= self.valid_from && date <= self.valid_until
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CertificateStoreRoot {
+    certificates: Vec<RecycledContentCertificate>,
+}
+
+#[derive(Debug, Error)]
+pub enum CertificateRegistryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("CSV parse error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Value out of allowed range: {0}")]
+    Range(String),
+    #[error("Duplicate certificate id {0}")]
+    DuplicateId(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CertificateIssueKind {
+    /// Not present in the registry.
+    Unknown,
+    /// Shipment date is after the validity window.
+    Expired,
+    /// Shipment date is before the validity window.
+    NotYetValid,
+    /// Issued to another sawmill or for another material.
+    OutOfScope,
+    /// Covered mass already fully claimed by earlier shipments.
+    OverAllocated,
+    /// Shipment has no date, so the validity window cannot be checked.
+    MissingShipDate,
+}
+
+/// A certificate reference that did not back (all of) a shipment's claim.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateIssue {
+    pub certificate_id: String,
+    pub shipment_id: String,
+    pub kind: CertificateIssueKind,
+}
+
+/// Result of allocating certificate mass to shipments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CertificateAllocation {
+    /// Certified mass credited to each shipment id.
+    pub backed_mass_kg: HashMap<String, f32>,
+    /// Mass drawn from each certificate id.
+    pub allocated_mass_kg: HashMap<String, f32>,
+    pub issues: Vec<CertificateIssue>,
+}
+
+impl CertificateAllocation {
+    pub fn backed_mass_for(&self, shipment_id: &str) -> f32 {
+        self.backed_mass_kg.get(shipment_id).copied().unwrap_or(0.0)
+    }
+}
+
+/// Local store of PCR/PIR certificates keyed by certificate id.
+#[derive(Debug, Clone, Default)]
+pub struct CertificateRegistry {
+    certificates: HashMap<String, RecycledContentCertificate>,
+}
+
+impl CertificateRegistry {
+    /// Load from a JSON file shaped `{ "certificates": [ ... ] }`.
+    pub fn load_from_json<P: AsRef<Path>>(path: P) -> Result<Self, CertificateRegistryError> {
+        let mut buf = String::new();
+        File::open(path.as_ref())?.read_to_string(&mut buf)?;
+        let root: CertificateStoreRoot = serde_json::from_str(&buf)?;
+        Self::from_certificates(root.certificates)
+    }
+
+    /// Load from a CSV file with one certificate per row and headers matching
+    /// the `RecycledContentCertificate` field names.
+    pub fn load_from_csv<P: AsRef<Path>>(path: P) -> Result<Self, CertificateRegistryError> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(File::open(path.as_ref())?);
+        let mut certificates = Vec::new();
+        for result in rdr.deserialize::<RecycledContentCertificate>() {
+            certificates.push(result?);
+        }
+        Self::from_certificates(certificates)
+    }
+
+    pub fn from_certificates(
+        certificates: Vec<RecycledContentCertificate>,
+    ) -> Result<Self, CertificateRegistryError> {
+        let mut map = HashMap::new();
+        for cert in certificates {
+            if cert.covered_mass_kg < 0.0 {
+                return Err(CertificateRegistryError::Range(format!(
+                    "negative covered mass for certificate {}",
+                    cert.certificate_id
+                )));
+            }
+            if cert.valid_until < cert.valid_from {
+                return Err(CertificateRegistryError::Range(format!(
+                    "validity window ends before it starts for certificate {}",
+                    cert.certificate_id
+                )));
+            }
+            if !cert.content_type.covers(cert.material) {
+                return Err(CertificateRegistryError::Range(format!(
+                    "{:?} certificate {} cannot cover {:?}",
+                    cert.content_type, cert.certificate_id, cert.material
+                )));
+            }
+            if map.contains_key(&cert.certificate_id) {
+                return Err(CertificateRegistryError::DuplicateId(cert.certificate_id));
+            }
+            map.insert(cert.certificate_id.clone(), cert);
+        }
+        Ok(CertificateRegistry { certificates: map })
+    }
+
+    pub fn get(&self, certificate_id: &str) -> Option<&RecycledContentCertificate> {
+        self.certificates.get(certificate_id)
+    }
+
+    /// Allocate certificate mass to recycled shipments in shipment-date order.
+    /// Each shipment draws from its listed certificates until its mass is
+    /// covered; a certificate never backs more than its covered mass in total.
+    /// Undated shipments draw nothing and report `MissingShipDate`. Backed
+    /// mass accumulates per shipment id, so a repeated id is credited for
+    /// every record.
+    pub fn allocate(&self, shipments: &[PackagingShipment]) -> CertificateAllocation {
+        let mut ordered: Vec<&PackagingShipment> = shipments
+            .iter()
+            .filter(|s| s.material.is_recycled())
+            .collect();
+        ordered.sort_by(|a, b| {
+            a.shipped_on
+                .cmp(&b.shipped_on)
+                .then(a.shipment_id.cmp(&b.shipment_id))
+        });
+
+        let mut allocation = CertificateAllocation::default();
+        for shipment in ordered {
+            let mut needed = shipment.mass_kg.max(0.0);
+            let mut backed = 0.0;
+            for cert_id in &shipment.certificate_ids {
+                let kind = match (self.certificates.get(cert_id), shipment.shipped_on) {
+                    (None, _) => Some(CertificateIssueKind::Unknown),
+                    (Some(cert), _)
+                        if cert.holder_id != shipment.sawmill_id
+                            || cert.material != shipment.material
+                            || !cert.content_type.covers(shipment.material) =>
+                    {
+                        Some(CertificateIssueKind::OutOfScope)
+                    }
+                    (Some(_), None) => Some(CertificateIssueKind::MissingShipDate),
+                    (Some(cert), Some(shipped_on)) if shipped_on > cert.valid_until => {
+                        Some(CertificateIssueKind::Expired)
+                    }
+                    (Some(cert), Some(shipped_on)) if shipped_on < cert.valid_from => {
+                        Some(CertificateIssueKind::NotYetValid)
+                    }
+                    (Some(cert), Some(_)) => {
+                        let used = allocation
+                            .allocated_mass_kg
+                            .entry(cert_id.clone())
+                            .or_insert(0.0);
+                        let remaining = cert.covered_mass_kg - *used;
+                        if needed <= 0.0 {
+                            None
+                        } else if remaining <= 0.0 {
+                            Some(CertificateIssueKind::OverAllocated)
+                        } else {
+                            let take = remaining.min(needed);
+                            *used += take;
+                            needed -= take;
+                            backed += take;
+                            None
+                        }
+                    }
+                };
+                if let Some(kind) = kind {
+                    allocation.issues.push(CertificateIssue {
+                        certificate_id: cert_id.clone(),
+                        shipment_id: shipment.shipment_id.clone(),
+                        kind,
+                    });
+                }
+            }
+            *allocation
+                .backed_mass_kg
+                .entry(shipment.shipment_id.clone())
+                .or_insert(0.0) += backed;
+        }
+        allocation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn certificate(
+        id: &str,
+        valid_until: &str,
+        covered_mass_kg: f32,
+    ) -> RecycledContentCertificate {
+        RecycledContentCertificate {
+            certificate_id: id.to_string(),
+            issuer: "Issuer".to_string(),
+            holder_id: "SAWMILL-1".to_string(),
+            content_type: RecycledContentType::Pcr,
+            material: PackagingMaterial::RecycledPlastic,
+            valid_from: date("2026-01-01"),
+            valid_until: date(valid_until),
+            covered_mass_kg,
+        }
+    }
+
+    fn shipment(id: &str, shipped_on: &str, mass_kg: f32, cert: &str) -> PackagingShipment {
+        PackagingShipment {
+            shipment_id: id.to_string(),
+            sawmill_id: "SAWMILL-1".to_string(),
+            reporting_period: "2026-Q1".to_string(),
+            shipped_on: Some(date(shipped_on)),
+            material: PackagingMaterial::RecycledPlastic,
+            mass_kg,
+            certificate_ids: vec![cert.to_string()],
+        }
+    }
+
+    fn registry(certs: Vec<RecycledContentCertificate>) -> CertificateRegistry {
+        CertificateRegistry::from_certificates(certs).unwrap()
+    }
+
+    #[test]
+    fn test_over_allocated_certificate() {
+        let reg = registry(vec![certificate("C1", "2026-12-31", 100.0)]);
+        let allocation = reg.allocate(&[
+            shipment("S1", "2026-02-01", 80.0, "C1"),
+            shipment("S2", "2026-02-02", 50.0, "C1"),
+            shipment("S3", "2026-02-03", 10.0, "C1"),
+        ]);
+        assert_eq!(allocation.backed_mass_for("S1"), 80.0);
+        assert_eq!(allocation.backed_mass_for("S2"), 20.0);
+        assert_eq!(allocation.backed_mass_for("S3"), 0.0);
+        assert_eq!(allocation.issues.len(), 1);
+        assert_eq!(allocation.issues[0].shipment_id, "S3");
+        assert_eq!(
+            allocation.issues[0].kind,
+            CertificateIssueKind::OverAllocated
+        );
+    }
+
+    #[test]
+    fn test_duplicate_shipment_ids_accumulate() {
+        let reg = registry(vec![certificate("C1", "2026-12-31", 100.0)]);
+        let allocation = reg.allocate(&[
+            shipment("S1", "2026-02-01", 30.0, "C1"),
+            shipment("S1", "2026-02-05", 40.0, "C1"),
+        ]);
+        assert_eq!(allocation.backed_mass_for("S1"), 70.0);
+        assert_eq!(allocation.allocated_mass_kg["C1"], 70.0);
+    }
+
+    #[test]
+    fn test_expired_certificate_backs_nothing() {
+        let reg = registry(vec![certificate("C1", "2026-01-31", 100.0)]);
+        let allocation = reg.allocate(&[shipment("S1", "2026-02-01", 10.0, "C1")]);
+        assert_eq!(allocation.backed_mass_for("S1"), 0.0);
+        assert_eq!(allocation.issues[0].kind, CertificateIssueKind::Expired);
+    }
+
+    #[test]
+    fn test_certificate_for_virgin_material_is_rejected() {
+        let mut cert = certificate("C1", "2026-12-31", 100.0);
+        cert.material = PackagingMaterial::VirginWood;
+        assert!(matches!(
+            CertificateRegistry::from_certificates(vec![cert]),
+            Err(CertificateRegistryError::Range(_))
+        ));
+    }
+}
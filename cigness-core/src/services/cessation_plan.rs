@@ -1,14 +1,20 @@
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::domain::health_risk::HealthRiskReport;
+use crate::domain::nicotine_product::NicotineEquivalence;
 use crate::domain::smoker_profile::{HealthRiskBand, SmokerProfile};
 
 /// High-level cessation strategy aligned with real-world clinical practice.
 /// This does not prescribe drugs or specific therapies; it structures
 /// intensity, monitoring, and referral flags.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "CessationPlanRecord")]
 pub struct CessationPlan {
     pub profile_id: String,
+    /// Packs/day (or pack-equivalents) the plan was built from. Required:
+    /// schedules and tolerances scale from it, so it must never default to 0.
+    pub baseline_packs_per_day: f32,
     /// Target reduction in packs/day over the next 90 days.
     pub target_packs_per_day_90d: f32,
     /// Whether immediate clinician or counseling referral is recommended.
@@ -17,11 +23,77 @@ pub struct CessationPlan {
     pub monitoring_interval_days: u32,
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum PlanFormatError {
+    #[error(
+        "Cessation plan for {profile_id} uses the v1 format without baseline_packs_per_day; \
+         rebuild it from the current profile"
+    )]
+    MissingBaseline { profile_id: String },
+}
+
+/// Stored form of `CessationPlan`. Plans saved before the baseline was
+/// recorded (v1) cannot be scheduled, so they are rejected by name rather
+/// than given a made-up baseline.
+#[derive(Debug, Deserialize)]
+struct CessationPlanRecord {
+    profile_id: String,
+    #[serde(default)]
+    baseline_packs_per_day: Option<f32>,
+    target_packs_per_day_90d: f32,
+    referral_recommended: bool,
+    monitoring_interval_days: u32,
+}
+
+impl TryFrom<CessationPlanRecord> for CessationPlan {
+    type Error = PlanFormatError;
+
+    fn try_from(record: CessationPlanRecord) -> Result<Self, Self::Error> {
+        let baseline_packs_per_day = match record.baseline_packs_per_day {
+            Some(b) => b,
+            None => {
+                return Err(PlanFormatError::MissingBaseline {
+                    profile_id: record.profile_id,
+                })
+            }
+        };
+        Ok(CessationPlan {
+            profile_id: record.profile_id,
+            baseline_packs_per_day,
+            target_packs_per_day_90d: record.target_packs_per_day_90d,
+            referral_recommended: record.referral_recommended,
+            monitoring_interval_days: record.monitoring_interval_days,
+        })
+    }
+}
+
 pub struct CessationPlanner;
 
 impl CessationPlanner {
     pub fn build_plan(profile: &SmokerProfile, risk: &HealthRiskReport) -> CessationPlan {
-        let baseline_ppd = profile.packs_per_day.max(0.0);
+        Self::build_plan_from_baseline(profile, risk, profile.packs_per_day)
+    }
+
+    /// Build a plan whose target is expressed in nicotine-equivalent
+    /// packs/day across every product the person uses.
+    pub fn build_plan_with(
+        profile: &SmokerProfile,
+        risk: &HealthRiskReport,
+        equivalence: &NicotineEquivalence,
+    ) -> CessationPlan {
+        Self::build_plan_from_baseline(
+            profile,
+            risk,
+            profile.packs_equivalent_per_day(equivalence),
+        )
+    }
+
+    fn build_plan_from_baseline(
+        profile: &SmokerProfile,
+        risk: &HealthRiskReport,
+        baseline_ppd: f32,
+    ) -> CessationPlan {
+        let baseline_ppd = baseline_ppd.max(0.0);
 
         // For high/critical risk, push toward rapid reduction and referral.
         let (target_ppd, referral, interval) = match risk.risk_band {
@@ -41,9 +113,36 @@ impl CessationPlanner {
 
         CessationPlan {
             profile_id: profile.profile_id.clone(),
+            baseline_packs_per_day: baseline_ppd,
             target_packs_per_day_90d: target_ppd.max(0.0),
             referral_recommended: referral || profile.clinician_recommended_cessation,
             monitoring_interval_days: interval,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_round_trips() {
+        let json = r#"{"profile_id": "p-1", "baseline_packs_per_day": 1.5,
+            "target_packs_per_day_90d": 0.75, "referral_recommended": false,
+            "monitoring_interval_days": 21}"#;
+        let plan: CessationPlan = serde_json::from_str(json).unwrap();
+        assert_eq!(plan.baseline_packs_per_day, 1.5);
+        let back: CessationPlan =
+            serde_json::from_str(&serde_json::to_string(&plan).unwrap()).unwrap();
+        assert_eq!(back.target_packs_per_day_90d, 0.75);
+    }
+
+    #[test]
+    fn test_v1_plan_without_baseline_is_rejected() {
+        let json = r#"{"profile_id": "p-1", "target_packs_per_day_90d": 0.75,
+            "referral_recommended": false, "monitoring_interval_days": 21}"#;
+        let err = serde_json::from_str::<CessationPlan>(json).unwrap_err();
+        assert!(err.to_string().contains("v1 format"));
+        assert!(err.to_string().contains("p-1"));
+    }
+}
@@ -0,0 +1,343 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::avoided_emissions::ConsumptionObservation;
+use crate::services::cessation_plan::CessationPlan;
+use crate::services::tapering_schedule::TaperingSchedule;
+
+/// Shortest monitoring interval a re-plan may propose.
+const MIN_MONITORING_INTERVAL_DAYS: u32 = 7;
+
+/// Tolerances for progress detection, as fractions of baseline packs/day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplanThresholds {
+    /// Observed above scheduled target by more than this is "falling behind".
+    pub behind_tolerance: f32,
+    /// Total change below this over `plateau_min_days` is a plateau.
+    pub plateau_tolerance: f32,
+    pub plateau_min_days: i64,
+    /// Rise between consecutive observations above this is a slip.
+    pub slip_tolerance: f32,
+}
+
+impl Default for ReplanThresholds {
+    fn default() -> Self {
+        ReplanThresholds {
+            behind_tolerance: 0.10,
+            plateau_tolerance: 0.05,
+            plateau_min_days: 14,
+            slip_tolerance: 0.20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProgressFinding {
+    FallingBehind {
+        at: DateTime<Utc>,
+        observed_packs_per_day: f32,
+        target_packs_per_day: f32,
+    },
+    Plateau {
+        since: DateTime<Utc>,
+        days: i64,
+    },
+    Slip {
+        at: DateTime<Utc>,
+        from_packs_per_day: f32,
+        to_packs_per_day: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlanAdjustment {
+    SlowerTaper { target_packs_per_day_90d: f32 },
+    MoreFrequentMonitoring { monitoring_interval_days: u32 },
+    Referral,
+}
+
+/// One proposed change with the reason shown to the counselor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedChange {
+    pub adjustment: PlanAdjustment,
+    pub reason: String,
+}
+
+/// A re-plan awaiting clinician sign-off. The adjusted plan is only
+/// released through `acknowledge`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplanProposal {
+    pub profile_id: String,
+    pub findings: Vec<ProgressFinding>,
+    pub changes: Vec<ProposedChange>,
+    proposed_plan: CessationPlan,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ReplanError {
+    #[error("A clinician reference is required to acknowledge a re-plan")]
+    MissingClinicianRef,
+}
+
+/// Record of who accepted a re-plan and when.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplanAcknowledgement {
+    pub profile_id: String,
+    /// Pseudonymous clinician reference.
+    pub clinician_ref: String,
+    pub acknowledged_at: DateTime<Utc>,
+    pub changes: Vec<ProposedChange>,
+}
+
+impl ReplanProposal {
+    /// Preview of the adjusted plan; not yet in effect.
+    pub fn proposed_plan(&self) -> &CessationPlan {
+        &self.proposed_plan
+    }
+
+    /// Accept the proposal, returning the plan to put into effect and the
+    /// acknowledgement to store alongside it. Fails if `clinician_ref` is
+    /// blank, since an unattributed sign-off is not an acknowledgement.
+    pub fn acknowledge(
+        self,
+        clinician_ref: String,
+        acknowledged_at: DateTime<Utc>,
+    ) -> Result<(CessationPlan, ReplanAcknowledgement), ReplanError> {
+        let clinician_ref = clinician_ref.trim();
+        if clinician_ref.is_empty() {
+            return Err(ReplanError::MissingClinicianRef);
+        }
+        let ack = ReplanAcknowledgement {
+            profile_id: self.profile_id,
+            clinician_ref: clinician_ref.to_string(),
+            acknowledged_at,
+            changes: self.changes,
+        };
+        Ok((self.proposed_plan, ack))
+    }
+}
+
+pub struct CessationReplanner;
+
+impl CessationReplanner {
+    /// Compare observations since the plan started against its schedule and
+    /// propose adjustments. Returns None when progress is on track, or when
+    /// the plan already has the strongest support a re-plan could add.
+    pub fn propose(
+        plan: &CessationPlan,
+        schedule: &TaperingSchedule,
+        observations: &[ConsumptionObservation],
+        thresholds: &ReplanThresholds,
+    ) -> Option<ReplanProposal> {
+        let findings = Self::detect(plan, schedule, observations, thresholds);
+        if findings.is_empty() {
+            return None;
+        }
+
+        let behind = findings
+            .iter()
+            .any(|f| matches!(f, ProgressFinding::FallingBehind { .. }));
+        let plateau = findings
+            .iter()
+            .any(|f| matches!(f, ProgressFinding::Plateau { .. }));
+        let slip = findings
+            .iter()
+            .any(|f| matches!(f, ProgressFinding::Slip { .. }));
+
+        let mut proposed = plan.clone();
+        let mut changes = Vec::new();
+
+        // Full-cessation plans keep their zero target; support is increased instead.
+        if (behind || plateau) && plan.target_packs_per_day_90d > 0.0 {
+            let baseline = plan
+                .baseline_packs_per_day
+                .max(plan.target_packs_per_day_90d);
+            let relaxed =
+                plan.target_packs_per_day_90d + (baseline - plan.target_packs_per_day_90d) * 0.5;
+            proposed.target_packs_per_day_90d = relaxed;
+            changes.push(ProposedChange {
+                adjustment: PlanAdjustment::SlowerTaper {
+                    target_packs_per_day_90d: relaxed,
+                },
+                reason: format!(
+                    "Consumption is not keeping pace with the schedule; easing the 90-day target from {:.2} to {:.2} packs/day.",
+                    plan.target_packs_per_day_90d, relaxed
+                ),
+            });
+        }
+
+        let shorter = (plan.monitoring_interval_days / 2).max(MIN_MONITORING_INTERVAL_DAYS);
+        if shorter < plan.monitoring_interval_days {
+            proposed.monitoring_interval_days = shorter;
+            changes.push(ProposedChange {
+                adjustment: PlanAdjustment::MoreFrequentMonitoring {
+                    monitoring_interval_days: shorter,
+                },
+                reason: format!(
+                    "Progress concerns detected; checking in every {} days instead of {}.",
+                    shorter, plan.monitoring_interval_days
+                ),
+            });
+        }
+
+        let needs_referral = slip || (behind && plateau) || plan.target_packs_per_day_90d <= 0.0;
+        if needs_referral && !plan.referral_recommended {
+            proposed.referral_recommended = true;
+            changes.push(ProposedChange {
+                adjustment: PlanAdjustment::Referral,
+                reason: if slip {
+                    "Consumption rose between observations; counseling referral recommended."
+                        .to_string()
+                } else {
+                    "Sustained lack of progress; counseling referral recommended.".to_string()
+                },
+            });
+        }
+
+        if changes.is_empty() {
+            return None;
+        }
+
+        Some(ReplanProposal {
+            profile_id: plan.profile_id.clone(),
+            findings,
+            changes,
+            proposed_plan: proposed,
+        })
+    }
+
+    /// Detect falling behind, plateaus and slips in observations taken on
+    /// or after the schedule start.
+    pub fn detect(
+        plan: &CessationPlan,
+        schedule: &TaperingSchedule,
+        observations: &[ConsumptionObservation],
+        thresholds: &ReplanThresholds,
+    ) -> Vec<ProgressFinding> {
+        let mut obs: Vec<&ConsumptionObservation> = observations
+            .iter()
+            .filter(|o| o.at.date_naive() >= schedule.start_date)
+            .collect();
+        obs.sort_by_key(|o| o.at);
+
+        let scale = plan.baseline_packs_per_day.max(0.05);
+        let mut findings = Vec::new();
+
+        if let Some(latest) = obs.last() {
+            if let Some(target) = schedule.target_on(latest.at.date_naive()) {
+                if latest.packs_per_day - target > thresholds.behind_tolerance * scale {
+                    findings.push(ProgressFinding::FallingBehind {
+                        at: latest.at,
+                        observed_packs_per_day: latest.packs_per_day,
+                        target_packs_per_day: target,
+                    });
+                }
+            }
+
+            // Plateau: the trailing window spanning at least `plateau_min_days`
+            // stays within tolerance while the schedule still expects reduction.
+            let window_start = obs
+                .iter()
+                .rev()
+                .find(|o| (latest.at - o.at).num_days() >= thresholds.plateau_min_days);
+            if let Some(start) = window_start {
+                let flat = obs.iter().filter(|o| o.at >= start.at).all(|o| {
+                    (o.packs_per_day - start.packs_per_day).abs()
+                        <= thresholds.plateau_tolerance * scale
+                });
+                let still_above_target = latest.packs_per_day > plan.target_packs_per_day_90d;
+                if flat && still_above_target {
+                    findings.push(ProgressFinding::Plateau {
+                        since: start.at,
+                        days: (latest.at - start.at).num_days(),
+                    });
+                }
+            }
+        }
+
+        for pair in obs.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            if next.packs_per_day - prev.packs_per_day > thresholds.slip_tolerance * scale {
+                findings.push(ProgressFinding::Slip {
+                    at: next.at,
+                    from_packs_per_day: prev.packs_per_day,
+                    to_packs_per_day: next.packs_per_day,
+                });
+            }
+        }
+
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::tapering_schedule::{TaperShape, TaperingScheduler};
+    use chrono::{Duration, NaiveDate, TimeZone};
+
+    fn plan(
+        target_packs_per_day_90d: f32,
+        referral_recommended: bool,
+        interval: u32,
+    ) -> CessationPlan {
+        CessationPlan {
+            profile_id: "p-1".to_string(),
+            baseline_packs_per_day: 1.0,
+            target_packs_per_day_90d,
+            referral_recommended,
+            monitoring_interval_days: interval,
+        }
+    }
+
+    /// Observations that rise sharply between day 1 and day 10.
+    fn slipping() -> Vec<ConsumptionObservation> {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        [(1, 0.8), (10, 1.2)]
+            .iter()
+            .map(|(d, ppd)| ConsumptionObservation {
+                at: start + Duration::days(*d),
+                packs_per_day: *ppd,
+            })
+            .collect()
+    }
+
+    fn propose(plan: &CessationPlan) -> Option<ReplanProposal> {
+        let start = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let schedule =
+            TaperingScheduler::build_schedule_with_shape(plan, TaperShape::Linear, start, None);
+        CessationReplanner::propose(plan, &schedule, &slipping(), &ReplanThresholds::default())
+    }
+
+    #[test]
+    fn test_no_proposal_when_nothing_can_change() {
+        // Full cessation, weekly monitoring and a referral already in place.
+        assert!(propose(&plan(0.0, true, MIN_MONITORING_INTERVAL_DAYS)).is_none());
+    }
+
+    #[test]
+    fn test_acknowledge_requires_clinician_ref() {
+        let proposal = propose(&plan(0.5, false, 30)).unwrap();
+        assert!(!proposal.changes.is_empty());
+        assert_eq!(
+            proposal
+                .clone()
+                .acknowledge("  ".to_string(), Utc::now())
+                .unwrap_err(),
+            ReplanError::MissingClinicianRef
+        );
+        assert_eq!(
+            proposal
+                .clone()
+                .acknowledge(String::new(), Utc::now())
+                .unwrap_err(),
+            ReplanError::MissingClinicianRef
+        );
+        let (accepted, ack) = proposal
+            .acknowledge(" c-42 ".to_string(), Utc::now())
+            .unwrap();
+        assert_eq!(ack.clinician_ref, "c-42");
+        assert!(accepted.referral_recommended);
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::geo_grid::{GeoGridNode, NodeType};
+use crate::services::node_network::{LinkRelation, NodeNetwork};
+use crate::services::node_registry::{NodeProfile, NodeRegistry};
+use crate::services::production_planner::MNodeProduct;
+
+/// Daily processing limits per node. Nodes without an entry are treated
+/// as unconstrained.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeCapacities {
+    pub c_node_daily_capacity_kg: BTreeMap<String, f32>,
+    pub m_node_device_shells_per_day: BTreeMap<String, u32>,
+}
+
+impl NodeCapacities {
+    /// Capacities from C-node `processing_capabilities` and M-node
+    /// `outputs.daily_capacity` profiles.
+    pub fn from_registry(registry: &NodeRegistry) -> Self {
+        let mut caps = NodeCapacities::default();
+        for node in &registry.nodes {
+            match &node.profile {
+                NodeProfile::C(p) => {
+                    caps.c_node_daily_capacity_kg.insert(
+                        node.node_id.clone(),
+                        p.processing_capabilities.daily_capacity_kg.max(0.0),
+                    );
+                }
+                NodeProfile::M(p) => {
+                    if let Some(c) = p
+                        .outputs
+                        .daily_capacity
+                        .get(MNodeProduct::DeviceShell.capacity_key())
+                    {
+                        caps.m_node_device_shells_per_day
+                            .insert(node.node_id.clone(), *c);
+                    }
+                }
+                _ => {}
+            }
+        }
+        caps
+    }
+}
+
+/// Network state at the end of one simulated day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyFlowSnapshot {
+    /// 1-based day number.
+    pub day: u32,
+    /// Collected but not yet processed material at each C-node.
+    pub c_backlog_kg: BTreeMap<String, f32>,
+    /// Feedstock waiting at each M-node.
+    pub m_feedstock_kg: BTreeMap<String, f32>,
+    /// Devices delivered to R-nodes on this day.
+    pub devices_delivered: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CNodeFlowSummary {
+    pub node_id: String,
+    pub total_intake_kg: f32,
+    pub total_processed_kg: f32,
+    pub final_backlog_kg: f32,
+    /// Days on which intake exceeded processing capacity.
+    pub saturated_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MNodeFlowSummary {
+    pub node_id: String,
+    pub total_received_kg: f32,
+    pub devices_produced: u32,
+    pub final_feedstock_kg: f32,
+    /// Material shipped on the last day that had not arrived when the run
+    /// ended.
+    pub in_transit_kg: f32,
+    /// Devices produced with no R-node to ship to.
+    pub undelivered_devices: u32,
+    /// Days on which production was limited by capacity, not feedstock.
+    pub saturated_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowSimulationReport {
+    pub days: Vec<DailyFlowSnapshot>,
+    pub c_nodes: Vec<CNodeFlowSummary>,
+    pub m_nodes: Vec<MNodeFlowSummary>,
+    /// Devices delivered per R-node over the whole run.
+    pub devices_delivered_per_r_node: BTreeMap<String, u32>,
+    /// Processed material at C-nodes with no material-flow link.
+    pub stranded_kg: f32,
+}
+
+pub struct FlowSimulator;
+
+impl FlowSimulator {
+    /// Step `days` days of C → M → R flow. Each day C-nodes take in their
+    /// average plastic intake, process up to capacity and split the output
+    /// evenly across their material-flow links; M-nodes turn feedstock into
+    /// device shells up to capacity and split them across device-supply
+    /// links. Material moved on a day is available downstream the next day;
+    /// whatever is still on the way after the last day is reported as
+    /// `in_transit_kg`.
+    pub fn run(
+        network: &NodeNetwork,
+        nodes: &[GeoGridNode],
+        capacities: &NodeCapacities,
+        kg_per_device_shell: f32,
+        days: u32,
+    ) -> FlowSimulationReport {
+        let kg_per_shell = kg_per_device_shell.max(f32::EPSILON);
+        let intake: BTreeMap<&str, f32> = nodes
+            .iter()
+            .filter(|n| n.node_type == NodeType::CNode)
+            .map(|n| (n.node_id.as_str(), n.avg_plastic_intake_kg_per_day.max(0.0)))
+            .collect();
+
+        let c_ids = Self::ids_of(network, NodeType::CNode);
+        let m_ids = Self::ids_of(network, NodeType::MNode);
+        let r_ids = Self::ids_of(network, NodeType::RNode);
+
+        let mut c_summary: BTreeMap<&str, CNodeFlowSummary> = c_ids
+            .iter()
+            .map(|id| {
+                (
+                    *id,
+                    CNodeFlowSummary {
+                        node_id: id.to_string(),
+                        total_intake_kg: 0.0,
+                        total_processed_kg: 0.0,
+                        final_backlog_kg: 0.0,
+                        saturated_days: 0,
+                    },
+                )
+            })
+            .collect();
+        let mut m_summary: BTreeMap<&str, MNodeFlowSummary> = m_ids
+            .iter()
+            .map(|id| {
+                (
+                    *id,
+                    MNodeFlowSummary {
+                        node_id: id.to_string(),
+                        total_received_kg: 0.0,
+                        devices_produced: 0,
+                        final_feedstock_kg: 0.0,
+                        in_transit_kg: 0.0,
+                        undelivered_devices: 0,
+                        saturated_days: 0,
+                    },
+                )
+            })
+            .collect();
+        let mut delivered: BTreeMap<String, u32> =
+            r_ids.iter().map(|id| (id.to_string(), 0)).collect();
+
+        let mut c_backlog: BTreeMap<&str, f32> = c_ids.iter().map(|id| (*id, 0.0)).collect();
+        let mut m_feedstock: BTreeMap<&str, f32> = m_ids.iter().map(|id| (*id, 0.0)).collect();
+        let mut in_transit: BTreeMap<&str, f32> = BTreeMap::new();
+        let mut stranded_kg = 0.0;
+        let mut snapshots = Vec::with_capacity(days as usize);
+
+        for day in 1..=days {
+            for (id, kg) in std::mem::take(&mut in_transit) {
+                if let Some(stock) = m_feedstock.get_mut(id) {
+                    *stock += kg;
+                }
+                if let Some(s) = m_summary.get_mut(id) {
+                    s.total_received_kg += kg;
+                }
+            }
+
+            for id in &c_ids {
+                let arriving = intake.get(id).copied().unwrap_or(0.0);
+                let backlog = c_backlog.get_mut(id).expect("initialised above");
+                *backlog += arriving;
+                let capacity = capacities
+                    .c_node_daily_capacity_kg
+                    .get(*id)
+                    .copied()
+                    .unwrap_or(f32::INFINITY);
+                let processed = backlog.min(capacity);
+                *backlog -= processed;
+
+                let summary = c_summary.get_mut(id).expect("initialised above");
+                summary.total_intake_kg += arriving;
+                summary.total_processed_kg += processed;
+                if *backlog > 0.0 {
+                    summary.saturated_days += 1;
+                }
+
+                let targets =
+                    Self::targets(network, id, LinkRelation::MaterialFlow, NodeType::MNode);
+                if targets.is_empty() {
+                    stranded_kg += processed;
+                } else {
+                    let share = processed / targets.len() as f32;
+                    for t in targets {
+                        *in_transit.entry(t).or_insert(0.0) += share;
+                    }
+                }
+            }
+
+            let mut delivered_today = 0u32;
+            for id in &m_ids {
+                let stock = m_feedstock.get_mut(id).expect("initialised above");
+                let possible = (*stock / kg_per_shell).floor() as u32;
+                let capacity = capacities
+                    .m_node_device_shells_per_day
+                    .get(*id)
+                    .copied()
+                    .unwrap_or(u32::MAX);
+                let produced = possible.min(capacity);
+                *stock -= produced as f32 * kg_per_shell;
+
+                let summary = m_summary.get_mut(id).expect("initialised above");
+                summary.devices_produced += produced;
+                if possible > capacity {
+                    summary.saturated_days += 1;
+                }
+
+                let targets =
+                    Self::targets(network, id, LinkRelation::DeviceSupply, NodeType::RNode);
+                if targets.is_empty() {
+                    summary.undelivered_devices += produced;
+                    continue;
+                }
+                // Rotate who gets the remainder so small batches spread out.
+                let n = targets.len() as u32;
+                let (base, remainder) = (produced / n, produced % n);
+                for (i, t) in targets.iter().enumerate() {
+                    let slot = (i as u32 + n - day % n) % n;
+                    let count = base + u32::from(slot < remainder);
+                    *delivered.entry(t.to_string()).or_insert(0) += count;
+                    delivered_today += count;
+                }
+            }
+
+            snapshots.push(DailyFlowSnapshot {
+                day,
+                c_backlog_kg: c_backlog.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+                m_feedstock_kg: m_feedstock
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), *v))
+                    .collect(),
+                devices_delivered: delivered_today,
+            });
+        }
+
+        for (id, s) in c_summary.iter_mut() {
+            s.final_backlog_kg = c_backlog[id];
+        }
+        for (id, s) in m_summary.iter_mut() {
+            s.final_feedstock_kg = m_feedstock[id];
+            s.in_transit_kg = in_transit.get(id).copied().unwrap_or(0.0);
+        }
+
+        FlowSimulationReport {
+            days: snapshots,
+            c_nodes: c_summary.into_values().collect(),
+            m_nodes: m_summary.into_values().collect(),
+            devices_delivered_per_r_node: delivered,
+            stranded_kg,
+        }
+    }
+
+    fn ids_of(network: &NodeNetwork, node_type: NodeType) -> Vec<&str> {
+        network
+            .node_types
+            .iter()
+            .filter(|(_, t)| **t == node_type)
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    /// Sorted, de-duplicated link targets of the given type.
+    fn targets<'a>(
+        network: &'a NodeNetwork,
+        from: &str,
+        relation: LinkRelation,
+        node_type: NodeType,
+    ) -> Vec<&'a str> {
+        let mut targets: Vec<&str> = network
+            .links
+            .iter()
+            .filter(|l| l.relation == relation && l.from == from)
+            .map(|l| l.to.as_str())
+            .filter(|to| network.node_type(to) == Some(node_type))
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        targets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::node_network::NodeLink;
+
+    fn node(node_id: &str, node_type: NodeType, intake_kg: f32) -> GeoGridNode {
+        GeoGridNode {
+            node_id: node_id.to_string(),
+            node_type,
+            latitude: 0.0,
+            longitude: 0.0,
+            avg_smoker_visits_per_day: 0,
+            avg_plastic_intake_kg_per_day: intake_kg,
+        }
+    }
+
+    fn link(from: &str, to: &str, relation: LinkRelation) -> NodeLink {
+        NodeLink {
+            from: from.to_string(),
+            to: to.to_string(),
+            relation,
+        }
+    }
+
+    /// C1 (10 kg/day, capacity 6) feeds M1 and M2; C2 (4 kg/day) feeds M1
+    /// only. M3 has no suppliers.
+    fn fixture() -> (NodeNetwork, Vec<GeoGridNode>, NodeCapacities) {
+        let nodes = vec![
+            node("C1", NodeType::CNode, 10.0),
+            node("C2", NodeType::CNode, 4.0),
+            node("M1", NodeType::MNode, 0.0),
+            node("M2", NodeType::MNode, 0.0),
+            node("M3", NodeType::MNode, 0.0),
+            node("R1", NodeType::RNode, 0.0),
+        ];
+        let links = vec![
+            link("C1", "M1", LinkRelation::MaterialFlow),
+            link("C1", "M2", LinkRelation::MaterialFlow),
+            link("C2", "M1", LinkRelation::MaterialFlow),
+            link("M1", "R1", LinkRelation::DeviceSupply),
+        ];
+        let network = NodeNetwork::from_nodes(&nodes, links).unwrap();
+        let mut capacities = NodeCapacities::default();
+        capacities
+            .c_node_daily_capacity_kg
+            .insert("C1".to_string(), 6.0);
+        (network, nodes, capacities)
+    }
+
+    fn m_node<'a>(report: &'a FlowSimulationReport, id: &str) -> &'a MNodeFlowSummary {
+        report.m_nodes.iter().find(|m| m.node_id == id).unwrap()
+    }
+
+    #[test]
+    fn test_in_transit_is_last_day_shipments_per_m_node() {
+        let (network, nodes, capacities) = fixture();
+        let report = FlowSimulator::run(&network, &nodes, &capacities, 1.0, 3);
+
+        // Last day: C1 splits 6 kg across M1 and M2; C2 sends 4 kg to M1.
+        assert_eq!(m_node(&report, "M1").in_transit_kg, 7.0);
+        assert_eq!(m_node(&report, "M2").in_transit_kg, 3.0);
+        assert_eq!(m_node(&report, "M3").in_transit_kg, 0.0);
+
+        // Earlier days' shipments have arrived.
+        assert_eq!(m_node(&report, "M1").total_received_kg, 14.0);
+        assert_eq!(m_node(&report, "M2").total_received_kg, 6.0);
+    }
+
+    #[test]
+    fn test_processed_mass_is_received_or_in_transit() {
+        let (network, nodes, capacities) = fixture();
+        let report = FlowSimulator::run(&network, &nodes, &capacities, 1.0, 5);
+        let processed: f32 = report.c_nodes.iter().map(|c| c.total_processed_kg).sum();
+        let received: f32 = report.m_nodes.iter().map(|m| m.total_received_kg).sum();
+        let in_transit: f32 = report.m_nodes.iter().map(|m| m.in_transit_kg).sum();
+        assert_eq!(report.stranded_kg, 0.0);
+        assert!((processed - received - in_transit).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_single_day_run_has_nothing_received() {
+        let (network, nodes, capacities) = fixture();
+        let report = FlowSimulator::run(&network, &nodes, &capacities, 1.0, 1);
+        assert_eq!(m_node(&report, "M1").total_received_kg, 0.0);
+        assert_eq!(m_node(&report, "M1").in_transit_kg, 7.0);
+
+        let report = FlowSimulator::run(&network, &nodes, &capacities, 1.0, 0);
+        assert!(report.m_nodes.iter().all(|m| m.in_transit_kg == 0.0));
+    }
+}
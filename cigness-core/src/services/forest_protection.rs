@@ -1,5 +1,10 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::services::certificate_registry::{CertificateIssue, CertificateRegistry};
+
 /// Configuration rule for sawmills that participate in the Cigness ecosystem.
 /// This maps to real policy statements: e.g., "no virgin-wood packaging".
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +25,99 @@ pub struct SawmillComplianceStatus {
     pub effective_recycled_content_ratio: f32,
 }
 
+/// Packaging material used on a shipment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackagingMaterial {
+    VirginWood,
+    VirginPlastic,
+    RecycledPlastic,
+    RecycledPaper,
+    Other,
+}
+
+impl PackagingMaterial {
+    pub fn is_recycled(&self) -> bool {
+        matches!(
+            self,
+            PackagingMaterial::RecycledPlastic | PackagingMaterial::RecycledPaper
+        )
+    }
+}
+
+/// Evidence record for the packaging on one outbound shipment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackagingShipment {
+    pub shipment_id: String,
+    pub sawmill_id: String,
+    /// Reporting period label, e.g. "2026-Q1" or "2026-03".
+    pub reporting_period: String,
+    /// Absent in older records; such shipments cannot back certified content.
+    #[serde(default)]
+    pub shipped_on: Option<NaiveDate>,
+    pub material: PackagingMaterial,
+    pub mass_kg: f32,
+    /// PCR/PIR certificate ids backing a recycled-content claim.
+    #[serde(default)]
+    pub certificate_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SawmillRule {
+    RequireRecycledPackaging,
+    ForbidVirginWoodPackaging,
+    MinRecycledContentRatio,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleResult {
+    pub rule: SawmillRule,
+    /// False only when the rule is enabled and not met.
+    pub passed: bool,
+    /// True when the policy enables this rule.
+    pub enforced: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceViolation {
+    pub rule: SawmillRule,
+    /// Offending shipment, or None for aggregate rules.
+    pub shipment_id: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SawmillComplianceReport {
+    pub status: SawmillComplianceStatus,
+    pub total_packaging_mass_kg: f32,
+    /// Recycled mass counted as traceable to PCR/PIR sources.
+    pub traceable_recycled_mass_kg: f32,
+    pub rule_results: Vec<RuleResult>,
+    pub violations: Vec<ComplianceViolation>,
+    /// Certificate references that were not counted; empty unless the
+    /// report was built against a certificate registry.
+    #[serde(default)]
+    pub certificate_issues: Vec<CertificateIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodCompliance {
+    pub reporting_period: String,
+    pub compliant: bool,
+    pub effective_recycled_content_ratio: f32,
+    pub violation_count: usize,
+}
+
+impl PeriodCompliance {
+    fn from_report(reporting_period: &str, report: &SawmillComplianceReport) -> Self {
+        PeriodCompliance {
+            reporting_period: reporting_period.to_string(),
+            compliant: report.status.compliant,
+            effective_recycled_content_ratio: report.status.effective_recycled_content_ratio,
+            violation_count: report.violations.len(),
+        }
+    }
+}
+
 pub struct ForestProtectionService;
 
 impl ForestProtectionService {
@@ -36,4 +134,153 @@ impl ForestProtectionService {
             effective_recycled_content_ratio: ratio,
         }
     }
+
+    /// Evaluate every policy rule against shipment evidence for the policy's
+    /// sawmill. Shipments for other sawmills are ignored. Recycled mass only
+    /// counts when backed by registry certificates that are in scope, valid
+    /// on the shipment date and not already claimed by earlier shipments.
+    pub fn evaluate_shipments(
+        policy: &SawmillPolicy,
+        shipments: &[PackagingShipment],
+        registry: &CertificateRegistry,
+    ) -> SawmillComplianceReport {
+        let own: Vec<PackagingShipment> = shipments
+            .iter()
+            .filter(|s| s.sawmill_id == policy.sawmill_id)
+            .cloned()
+            .collect();
+        let allocation = registry.allocate(&own);
+        let traceable_recycled_mass_kg: f32 = allocation.backed_mass_kg.values().sum();
+        let own_refs: Vec<&PackagingShipment> = own.iter().collect();
+        Self::build_report(
+            policy,
+            &own_refs,
+            traceable_recycled_mass_kg,
+            allocation.issues,
+        )
+    }
+
+    fn build_report(
+        policy: &SawmillPolicy,
+        own: &[&PackagingShipment],
+        traceable_recycled_mass_kg: f32,
+        certificate_issues: Vec<CertificateIssue>,
+    ) -> SawmillComplianceReport {
+        let total_packaging_mass_kg: f32 = own.iter().map(|s| s.mass_kg.max(0.0)).sum();
+        let ratio = if total_packaging_mass_kg > 0.0 {
+            traceable_recycled_mass_kg / total_packaging_mass_kg
+        } else {
+            0.0
+        };
+
+        let mut violations = Vec::new();
+
+        if policy.require_recycled_packaging {
+            for s in own.iter().filter(|s| !s.material.is_recycled()) {
+                violations.push(ComplianceViolation {
+                    rule: SawmillRule::RequireRecycledPackaging,
+                    shipment_id: Some(s.shipment_id.clone()),
+                    description: format!("{:?} packaging is not recycled", s.material),
+                });
+            }
+        }
+        if policy.forbid_virgin_wood_packaging {
+            for s in own
+                .iter()
+                .filter(|s| s.material == PackagingMaterial::VirginWood)
+            {
+                violations.push(ComplianceViolation {
+                    rule: SawmillRule::ForbidVirginWoodPackaging,
+                    shipment_id: Some(s.shipment_id.clone()),
+                    description: "virgin wood packaging is forbidden".to_string(),
+                });
+            }
+        }
+        if ratio < policy.min_recycled_content_ratio {
+            violations.push(ComplianceViolation {
+                rule: SawmillRule::MinRecycledContentRatio,
+                shipment_id: None,
+                description: format!(
+                    "traceable recycled content {:.2} below required {:.2}",
+                    ratio, policy.min_recycled_content_ratio
+                ),
+            });
+        }
+
+        let rule_results = [
+            (
+                SawmillRule::RequireRecycledPackaging,
+                policy.require_recycled_packaging,
+            ),
+            (
+                SawmillRule::ForbidVirginWoodPackaging,
+                policy.forbid_virgin_wood_packaging,
+            ),
+            (SawmillRule::MinRecycledContentRatio, true),
+        ]
+        .iter()
+        .map(|(rule, enforced)| RuleResult {
+            rule: *rule,
+            enforced: *enforced,
+            passed: !violations.iter().any(|v| v.rule == *rule),
+        })
+        .collect();
+
+        SawmillComplianceReport {
+            status: SawmillComplianceStatus {
+                sawmill_id: policy.sawmill_id.clone(),
+                compliant: violations.is_empty(),
+                effective_recycled_content_ratio: ratio,
+            },
+            total_packaging_mass_kg,
+            traceable_recycled_mass_kg,
+            rule_results,
+            violations,
+            certificate_issues,
+        }
+    }
+
+    /// Per-period compliance, ordered by reporting period label. Certificate
+    /// mass is allocated once across all periods in shipment-date order, so
+    /// a certificate claimed in one period is not counted again in a later
+    /// one.
+    pub fn compliance_trend(
+        policy: &SawmillPolicy,
+        shipments: &[PackagingShipment],
+        registry: &CertificateRegistry,
+    ) -> Vec<PeriodCompliance> {
+        let own: Vec<PackagingShipment> = shipments
+            .iter()
+            .filter(|s| s.sawmill_id == policy.sawmill_id)
+            .cloned()
+            .collect();
+        let allocation = registry.allocate(&own);
+
+        let mut by_period: BTreeMap<&str, Vec<&PackagingShipment>> = BTreeMap::new();
+        for s in &own {
+            by_period
+                .entry(s.reporting_period.as_str())
+                .or_default()
+                .push(s);
+        }
+
+        by_period
+            .into_iter()
+            .map(|(period, period_shipments)| {
+                let ids: BTreeSet<&str> = period_shipments
+                    .iter()
+                    .map(|s| s.shipment_id.as_str())
+                    .collect();
+                let traceable: f32 = ids.iter().map(|id| allocation.backed_mass_for(id)).sum();
+                let issues = allocation
+                    .issues
+                    .iter()
+                    .filter(|i| ids.contains(i.shipment_id.as_str()))
+                    .cloned()
+                    .collect();
+                let report = Self::build_report(policy, &period_shipments, traceable, issues);
+                PeriodCompliance::from_report(period, &report)
+            })
+            .collect()
+    }
 }
@@ -1,14 +1,28 @@
 use serde::{Deserialize, Serialize};
 
+/// Mean Earth radius (km) used for great-circle distances.
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Great-circle distance in km between two WGS84 points.
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
 /// GeoGridNode represents a physical node in the Cigness grid:
 /// - R-Node: recycling + butt collection
 /// - M-Node: micro-manufacturing
 /// - C-Node: circularity / device trade-in
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// - P-Node: policy zone anchor (retail density, smoke-free zones)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NodeType {
     RNode, // Recycling node
     MNode, // Micro-manufacturing node
     CNode, // Circularity node
+    PNode, // Policy zone node
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,15 +39,50 @@ pub struct GeoGridNode {
     pub avg_plastic_intake_kg_per_day: f32,
 }
 
+impl GeoGridNode {
+    pub fn distance_km_to(&self, latitude: f64, longitude: f64) -> f64 {
+        haversine_km(self.latitude, self.longitude, latitude, longitude)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeoGridSummary {
     pub total_nodes: usize,
     pub total_r_nodes: usize,
     pub total_m_nodes: usize,
     pub total_c_nodes: usize,
+    pub total_p_nodes: usize,
     pub total_plastic_intake_kg_per_day: f32,
 }
 
+/// A square grid cell with resident population, e.g. from census tracts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopulatedCell {
+    pub cell_id: String,
+    pub center_latitude: f64,
+    pub center_longitude: f64,
+    /// Edge length of the cell (km).
+    pub size_km: f64,
+    pub population: u32,
+}
+
+/// A populated cell with no R-node within the coverage radius.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageGap {
+    pub cell: PopulatedCell,
+    pub nearest_r_node_id: Option<String>,
+    pub nearest_r_node_km: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub radius_km: f64,
+    pub covered_population: u64,
+    pub uncovered_population: u64,
+    /// Uncovered cells, most populated first.
+    pub gaps: Vec<CoverageGap>,
+}
+
 pub struct GeoGridAnalyzer;
 
 impl GeoGridAnalyzer {
@@ -41,6 +90,7 @@ impl GeoGridAnalyzer {
         let mut total_r = 0usize;
         let mut total_m = 0usize;
         let mut total_c = 0usize;
+        let mut total_p = 0usize;
         let mut plastic_total = 0.0_f32;
 
         for n in nodes {
@@ -48,6 +98,7 @@ impl GeoGridAnalyzer {
                 NodeType::RNode => total_r += 1,
                 NodeType::MNode => total_m += 1,
                 NodeType::CNode => total_c += 1,
+                NodeType::PNode => total_p += 1,
             }
             plastic_total += n.avg_plastic_intake_kg_per_day.max(0.0);
         }
@@ -57,7 +108,83 @@ impl GeoGridAnalyzer {
             total_r_nodes: total_r,
             total_m_nodes: total_m,
             total_c_nodes: total_c,
+            total_p_nodes: total_p,
             total_plastic_intake_kg_per_day: plastic_total,
         }
     }
+
+    /// Closest node of `node_type` to a point, with its distance in km.
+    pub fn nearest_node(
+        nodes: &[GeoGridNode],
+        latitude: f64,
+        longitude: f64,
+        node_type: NodeType,
+    ) -> Option<(&GeoGridNode, f64)> {
+        nodes
+            .iter()
+            .filter(|n| n.node_type == node_type)
+            .map(|n| (n, n.distance_km_to(latitude, longitude)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Nodes within `radius_km` of a point, nearest first.
+    pub fn nodes_within_radius(
+        nodes: &[GeoGridNode],
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+    ) -> Vec<(&GeoGridNode, f64)> {
+        let mut within: Vec<(&GeoGridNode, f64)> = nodes
+            .iter()
+            .map(|n| (n, n.distance_km_to(latitude, longitude)))
+            .filter(|(_, d)| *d <= radius_km)
+            .collect();
+        within.sort_by(|a, b| a.1.total_cmp(&b.1));
+        within
+    }
+
+    /// Populated cells whose centre has no R-node within `radius_km`.
+    /// Cells with zero population are ignored.
+    pub fn coverage_gaps(
+        nodes: &[GeoGridNode],
+        cells: &[PopulatedCell],
+        radius_km: f64,
+    ) -> CoverageReport {
+        let mut covered_population = 0u64;
+        let mut uncovered_population = 0u64;
+        let mut gaps = Vec::new();
+
+        for cell in cells.iter().filter(|c| c.population > 0) {
+            let nearest = Self::nearest_node(
+                nodes,
+                cell.center_latitude,
+                cell.center_longitude,
+                NodeType::RNode,
+            );
+            match nearest {
+                Some((_, d)) if d <= radius_km => covered_population += cell.population as u64,
+                _ => {
+                    uncovered_population += cell.population as u64;
+                    gaps.push(CoverageGap {
+                        cell: cell.clone(),
+                        nearest_r_node_id: nearest.map(|(n, _)| n.node_id.clone()),
+                        nearest_r_node_km: nearest.map(|(_, d)| d),
+                    });
+                }
+            }
+        }
+        gaps.sort_by(|a, b| {
+            b.cell
+                .population
+                .cmp(&a.cell.population)
+                .then(a.cell.cell_id.cmp(&b.cell.cell_id))
+        });
+
+        CoverageReport {
+            radius_km,
+            covered_population,
+            uncovered_population,
+            gaps,
+        }
+    }
 }
@@ -0,0 +1,531 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::services::geo_grid::{CoverageReport, GeoGridNode, NodeType, EARTH_RADIUS_KM};
+
+/// Vertices used to approximate a coverage circle.
+const CIRCLE_SEGMENTS: usize = 36;
+
+#[derive(Debug, Error)]
+pub enum KmlError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Placemark {0} has no Point coordinates")]
+    MissingCoordinates(String),
+    #[error("Placemark {placemark} has invalid coordinates: {value}")]
+    InvalidCoordinates { placemark: String, value: String },
+    #[error("Placemark {placemark} has invalid {field}: {value}")]
+    InvalidValue {
+        placemark: String,
+        field: String,
+        value: String,
+    },
+}
+
+/// Placemark that was read but not turned into a node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedPlacemark {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KmlImport {
+    pub nodes: Vec<GeoGridNode>,
+    pub skipped: Vec<SkippedPlacemark>,
+}
+
+/// Analysis layer written alongside the nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum KmlOverlay {
+    CoverageCircle {
+        name: String,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+    },
+    GapCell {
+        name: String,
+        center_latitude: f64,
+        center_longitude: f64,
+        size_km: f64,
+        population: u32,
+    },
+}
+
+impl KmlOverlay {
+    /// Coverage circles around every R-node plus one cell per gap.
+    pub fn from_coverage(nodes: &[GeoGridNode], report: &CoverageReport) -> Vec<KmlOverlay> {
+        let circles = nodes
+            .iter()
+            .filter(|n| n.node_type == NodeType::RNode)
+            .map(|n| KmlOverlay::CoverageCircle {
+                name: format!("{} coverage", n.node_id),
+                latitude: n.latitude,
+                longitude: n.longitude,
+                radius_km: report.radius_km,
+            });
+        let gaps = report.gaps.iter().map(|g| KmlOverlay::GapCell {
+            name: format!("Gap {}", g.cell.cell_id),
+            center_latitude: g.cell.center_latitude,
+            center_longitude: g.cell.center_longitude,
+            size_km: g.cell.size_km,
+            population: g.cell.population,
+        });
+        circles.chain(gaps).collect()
+    }
+}
+
+/// Reads and writes geo-grid nodes as KML placemarks.
+#[derive(Debug, Clone)]
+pub struct GeoGridKml {
+    /// Style id (without '#') to node type, used when a placemark has no
+    /// `node_type` data field.
+    pub style_types: HashMap<String, NodeType>,
+}
+
+impl Default for GeoGridKml {
+    fn default() -> Self {
+        let style_types = [
+            ("rnodeStyle", NodeType::RNode),
+            ("mnodeStyle", NodeType::MNode),
+            ("cnodeStyle", NodeType::CNode),
+            ("pnodeStyle", NodeType::PNode),
+        ]
+        .into_iter()
+        .map(|(id, t)| (id.to_string(), t))
+        .collect();
+        GeoGridKml { style_types }
+    }
+}
+
+impl GeoGridKml {
+    pub fn load_from_file<P: AsRef<Path>>(&self, path: P) -> Result<KmlImport, KmlError> {
+        let text = fs::read_to_string(path.as_ref())?;
+        self.parse(&text)
+    }
+
+    /// Parse every Placemark with a Point. The `node_type` data field wins
+    /// over the style id; placemarks of unknown type or without a Point
+    /// (e.g. a Polygon policy zone) are skipped.
+    pub fn parse(&self, kml: &str) -> Result<KmlImport, KmlError> {
+        let kml = strip_comments(kml);
+        let mut nodes = Vec::new();
+        let mut skipped = Vec::new();
+
+        for body in elements(&kml, "Placemark") {
+            let name = first_text(body, "name").unwrap_or_default();
+            let data = extended_data(body);
+            let node_id = data.get("node_id").cloned().unwrap_or_else(|| name.clone());
+            let label = if node_id.is_empty() {
+                "<unnamed>".to_string()
+            } else {
+                node_id.clone()
+            };
+
+            let from_data = data.get("node_type").map(|v| parse_node_type(v));
+            let from_style = first_text(body, "styleUrl")
+                .and_then(|url| self.style_types.get(url.trim_start_matches('#')).copied());
+            let node_type = match from_data {
+                Some(t) => t,
+                None => from_style,
+            };
+            let Some(node_type) = node_type else {
+                skipped.push(SkippedPlacemark {
+                    name: label,
+                    reason: match data.get("node_type") {
+                        Some(v) => format!("unsupported node_type {}", v),
+                        None => "no node_type data or known style".to_string(),
+                    },
+                });
+                continue;
+            };
+
+            let Some(point) = elements(body, "Point").next() else {
+                skipped.push(SkippedPlacemark {
+                    name: label,
+                    reason: "no Point geometry".to_string(),
+                });
+                continue;
+            };
+            let coords = first_text(point, "coordinates")
+                .ok_or(KmlError::MissingCoordinates(label.clone()))?;
+            let (longitude, latitude) =
+                parse_point(&coords).ok_or(KmlError::InvalidCoordinates {
+                    placemark: label.clone(),
+                    value: coords.clone(),
+                })?;
+
+            nodes.push(GeoGridNode {
+                node_id,
+                node_type,
+                latitude,
+                longitude,
+                avg_smoker_visits_per_day: parse_field(&data, "avg_smoker_visits_per_day", &label)?,
+                avg_plastic_intake_kg_per_day: parse_field(
+                    &data,
+                    "avg_plastic_intake_kg_per_day",
+                    &label,
+                )?,
+            });
+        }
+
+        Ok(KmlImport { nodes, skipped })
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        document_name: &str,
+        nodes: &[GeoGridNode],
+        overlays: &[KmlOverlay],
+    ) -> Result<(), KmlError> {
+        fs::write(path.as_ref(), self.to_kml(document_name, nodes, overlays))?;
+        Ok(())
+    }
+
+    /// Render nodes and overlays as a KML document that `parse` reads back.
+    pub fn to_kml(
+        &self,
+        document_name: &str,
+        nodes: &[GeoGridNode],
+        overlays: &[KmlOverlay],
+    ) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n  <Document>\n");
+        let _ = writeln!(out, "    <name>{}</name>", escape(document_name));
+
+        for t in [
+            NodeType::RNode,
+            NodeType::MNode,
+            NodeType::CNode,
+            NodeType::PNode,
+        ] {
+            let _ = writeln!(
+                out,
+                "    <Style id=\"{}\">\n      <IconStyle>\n        <color>{}</color>\n        <scale>1.2</scale>\n      </IconStyle>\n    </Style>",
+                self.style_id(t),
+                icon_color(t)
+            );
+        }
+        out.push_str("    <Style id=\"coverageStyle\">\n      <LineStyle><color>ff00aa00</color><width>1</width></LineStyle>\n      <PolyStyle><color>3300ff00</color></PolyStyle>\n    </Style>\n");
+        out.push_str("    <Style id=\"gapStyle\">\n      <LineStyle><color>ff0000ff</color><width>1</width></LineStyle>\n      <PolyStyle><color>660000ff</color></PolyStyle>\n    </Style>\n");
+
+        out.push_str("    <Folder>\n      <name>Nodes</name>\n");
+        for n in nodes {
+            let _ = writeln!(
+                out,
+                "      <Placemark>\n        <name>{id}</name>\n        <styleUrl>#{style}</styleUrl>\n        <ExtendedData>\n          <Data name=\"node_id\"><value>{id}</value></Data>\n          <Data name=\"node_type\"><value>{kind}</value></Data>\n          <Data name=\"avg_smoker_visits_per_day\"><value>{visits}</value></Data>\n          <Data name=\"avg_plastic_intake_kg_per_day\"><value>{intake}</value></Data>\n        </ExtendedData>\n        <Point>\n          <coordinates>{lon},{lat},0</coordinates>\n        </Point>\n      </Placemark>",
+                id = escape(&n.node_id),
+                style = self.style_id(n.node_type),
+                kind = node_type_code(n.node_type),
+                visits = n.avg_smoker_visits_per_day,
+                intake = n.avg_plastic_intake_kg_per_day,
+                lon = n.longitude,
+                lat = n.latitude,
+            );
+        }
+        out.push_str("    </Folder>\n");
+
+        if !overlays.is_empty() {
+            out.push_str("    <Folder>\n      <name>Analysis</name>\n");
+            for overlay in overlays {
+                let (name, style, ring) = match overlay {
+                    KmlOverlay::CoverageCircle {
+                        name,
+                        latitude,
+                        longitude,
+                        radius_km,
+                    } => (
+                        name.clone(),
+                        "coverageStyle",
+                        circle_ring(*latitude, *longitude, *radius_km),
+                    ),
+                    KmlOverlay::GapCell {
+                        name,
+                        center_latitude,
+                        center_longitude,
+                        size_km,
+                        population,
+                    } => (
+                        format!("{} (population {})", name, population),
+                        "gapStyle",
+                        square_ring(*center_latitude, *center_longitude, *size_km),
+                    ),
+                };
+                let coords: Vec<String> = ring
+                    .iter()
+                    .map(|(lat, lon)| format!("{:.6},{:.6},0", lon, lat))
+                    .collect();
+                let _ = writeln!(
+                    out,
+                    "      <Placemark>\n        <name>{}</name>\n        <styleUrl>#{}</styleUrl>\n        <Polygon>\n          <outerBoundaryIs><LinearRing><coordinates>{}</coordinates></LinearRing></outerBoundaryIs>\n        </Polygon>\n      </Placemark>",
+                    escape(&name),
+                    style,
+                    coords.join(" ")
+                );
+            }
+            out.push_str("    </Folder>\n");
+        }
+
+        out.push_str("  </Document>\n</kml>\n");
+        out
+    }
+
+    /// Style id for a node type, preferring the configured mapping.
+    fn style_id(&self, node_type: NodeType) -> String {
+        let mut ids: Vec<&String> = self
+            .style_types
+            .iter()
+            .filter(|(_, t)| **t == node_type)
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+        ids.first()
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| format!("{}Style", node_type_code(node_type).to_lowercase()))
+    }
+}
+
+/// Accepts "R-NODE", "R", "RNode", "r_node" and similar.
+fn parse_node_type(value: &str) -> Option<NodeType> {
+    let key: String = value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    match key.as_str() {
+        "R" | "RNODE" => Some(NodeType::RNode),
+        "M" | "MNODE" => Some(NodeType::MNode),
+        "C" | "CNODE" => Some(NodeType::CNode),
+        "P" | "PNODE" => Some(NodeType::PNode),
+        _ => None,
+    }
+}
+
+fn node_type_code(node_type: NodeType) -> &'static str {
+    match node_type {
+        NodeType::RNode => "R-NODE",
+        NodeType::MNode => "M-NODE",
+        NodeType::CNode => "C-NODE",
+        NodeType::PNode => "P-NODE",
+    }
+}
+
+fn icon_color(node_type: NodeType) -> &'static str {
+    match node_type {
+        NodeType::RNode => "ff00ff00",
+        NodeType::MNode => "ffffa500",
+        NodeType::CNode => "ff0000ff",
+        NodeType::PNode => "ffff00ff",
+    }
+}
+
+fn parse_field<T: std::str::FromStr + Default>(
+    data: &HashMap<String, String>,
+    field: &str,
+    placemark: &str,
+) -> Result<T, KmlError> {
+    match data.get(field) {
+        None => Ok(T::default()),
+        Some(v) => v.trim().parse().map_err(|_| KmlError::InvalidValue {
+            placemark: placemark.to_string(),
+            field: field.to_string(),
+            value: v.clone(),
+        }),
+    }
+}
+
+/// "lon,lat[,alt]" to (lon, lat).
+fn parse_point(coords: &str) -> Option<(f64, f64)> {
+    let first = coords.split_whitespace().next()?;
+    let mut parts = first.split(',');
+    let lon: f64 = parts.next()?.trim().parse().ok()?;
+    let lat: f64 = parts.next()?.trim().parse().ok()?;
+    if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+        return None;
+    }
+    Some((lon, lat))
+}
+
+/// Closed ring of (lat, lon) points approximating a circle.
+fn circle_ring(latitude: f64, longitude: f64, radius_km: f64) -> Vec<(f64, f64)> {
+    let delta = radius_km / EARTH_RADIUS_KM;
+    let (phi1, lambda1) = (latitude.to_radians(), longitude.to_radians());
+    (0..=CIRCLE_SEGMENTS)
+        .map(|i| {
+            let bearing =
+                (i % CIRCLE_SEGMENTS) as f64 * std::f64::consts::TAU / CIRCLE_SEGMENTS as f64;
+            let phi2 = (phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * bearing.cos()).asin();
+            let lambda2 = lambda1
+                + (bearing.sin() * delta.sin() * phi1.cos())
+                    .atan2(delta.cos() - phi1.sin() * phi2.sin());
+            (phi2.to_degrees(), lambda2.to_degrees())
+        })
+        .collect()
+}
+
+/// Closed ring of (lat, lon) points for a square cell.
+fn square_ring(latitude: f64, longitude: f64, size_km: f64) -> Vec<(f64, f64)> {
+    let half_lat = (size_km / 2.0 / EARTH_RADIUS_KM).to_degrees();
+    let half_lon = half_lat / latitude.to_radians().cos().max(1e-6);
+    vec![
+        (latitude - half_lat, longitude - half_lon),
+        (latitude - half_lat, longitude + half_lon),
+        (latitude + half_lat, longitude + half_lon),
+        (latitude + half_lat, longitude - half_lon),
+        (latitude - half_lat, longitude - half_lon),
+    ]
+}
+
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<!--") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Inner text of each `<tag ...>...</tag>` element (non-nesting tags only).
+fn elements<'a>(text: &'a str, tag: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut rest = text;
+    std::iter::from_fn(move || loop {
+        let start = rest.find(&open)?;
+        let after = &rest[start + open.len()..];
+        // Skip longer tag names sharing the prefix, e.g. <PointStyle>.
+        if !after.starts_with(['>', ' ', '\t', '\n', '\r', '/']) {
+            rest = after;
+            continue;
+        }
+        let gt = after.find('>')?;
+        if after[..gt].ends_with('/') {
+            rest = &after[gt + 1..];
+            return Some("");
+        }
+        let body = &after[gt + 1..];
+        let end = body.find(&close)?;
+        rest = &body[end + close.len()..];
+        return Some(&body[..end]);
+    })
+}
+
+fn first_text(text: &str, tag: &str) -> Option<String> {
+    elements(text, tag).next().map(|t| unescape(t.trim()))
+}
+
+/// `<Data name="k"><value>v</value></Data>` pairs; multi-line values are
+/// collapsed to single spaces.
+fn extended_data(placemark: &str) -> HashMap<String, String> {
+    let mut data = HashMap::new();
+    let mut rest = placemark;
+    while let Some(start) = rest.find("<Data ") {
+        let after = &rest[start..];
+        let Some(end) = after.find("</Data>") else {
+            break;
+        };
+        let element = &after[..end];
+        rest = &after[end + "</Data>".len()..];
+
+        let Some(name_start) = element.find("name=\"") else {
+            continue;
+        };
+        let name_rest = &element[name_start + 6..];
+        let Some(name_end) = name_rest.find('"') else {
+            continue;
+        };
+        let name = unescape(&name_rest[..name_end]);
+        if let Some(value) = elements(element, "value").next() {
+            let value = unescape(&value.split_whitespace().collect::<Vec<_>>().join(" "));
+            data.insert(name, value);
+        }
+    }
+    data
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trips(kml: &str) {
+        let kml_io = GeoGridKml::default();
+        let first = kml_io.parse(kml).unwrap();
+        assert!(!first.nodes.is_empty());
+
+        let written = kml_io.to_kml("round trip", &first.nodes, &[]);
+        let second = kml_io.parse(&written).unwrap();
+        assert!(second.skipped.is_empty());
+        assert_eq!(second.nodes.len(), first.nodes.len());
+        for (a, b) in first.nodes.iter().zip(&second.nodes) {
+            assert_eq!(a.node_id, b.node_id);
+            assert_eq!(a.node_type, b.node_type);
+            assert!((a.latitude - b.latitude).abs() < 1e-9);
+            assert!((a.longitude - b.longitude).abs() < 1e-9);
+            assert_eq!(a.avg_smoker_visits_per_day, b.avg_smoker_visits_per_day);
+            assert_eq!(
+                a.avg_plastic_intake_kg_per_day,
+                b.avg_plastic_intake_kg_per_day
+            );
+        }
+    }
+
+    #[test]
+    fn test_geo_grid_config_round_trips() {
+        assert_round_trips(include_str!("../../../configs/cigness.geo-grid.kml"));
+    }
+
+    #[test]
+    fn test_cigness_map_round_trips() {
+        assert_round_trips(include_str!("../../../src/maps/Cigness.kml"));
+    }
+
+    #[test]
+    fn test_placemark_without_point_is_skipped() {
+        let kml = r#"<kml><Document>
+            <Placemark>
+              <name>P_Zone_001</name>
+              <styleUrl>#pnodeStyle</styleUrl>
+              <Polygon><outerBoundaryIs><LinearRing>
+                <coordinates>-112.07,33.44,0 -112.06,33.44,0 -112.06,33.45,0 -112.07,33.44,0</coordinates>
+              </LinearRing></outerBoundaryIs></Polygon>
+            </Placemark>
+            <Placemark>
+              <name>R_Node_001</name>
+              <styleUrl>#rnodeStyle</styleUrl>
+              <Point><coordinates>-112.07,33.44,0</coordinates></Point>
+            </Placemark>
+        </Document></kml>"#;
+        let import = GeoGridKml::default().parse(kml).unwrap();
+        assert_eq!(import.nodes.len(), 1);
+        assert_eq!(import.skipped.len(), 1);
+        assert_eq!(import.skipped[0].name, "P_Zone_001");
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::plastic_recycling::DevicePlasticBatch;
+
+/// `energy_profile` section of m_node_profile.cigness.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManufacturingEnergyProfile {
+    /// Electricity per kg of shell feedstock processed (kWh/kg).
+    pub target_kwh_per_kg: f32,
+    /// Shift runs to the cleanest grid hours when accounting a batch.
+    pub renewable_preference: bool,
+    #[serde(default)]
+    pub load_smoothing_enabled: bool,
+}
+
+/// Grid carbon intensity for each hour of the day (local time, 0–23).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridIntensityProfile {
+    pub region_code: String,
+    pub kg_co2e_per_kwh_by_hour: [f32; 24],
+}
+
+impl GridIntensityProfile {
+    /// Mean intensity over `hours`; hours outside 0–23 are ignored.
+    /// Falls back to the daily mean when no valid hour is given.
+    pub fn mean_intensity(&self, hours: &[u8]) -> f32 {
+        let valid: Vec<f32> = hours
+            .iter()
+            .filter(|h| (**h as usize) < 24)
+            .map(|h| self.kg_co2e_per_kwh_by_hour[*h as usize].max(0.0))
+            .collect();
+        if valid.is_empty() {
+            return self
+                .kg_co2e_per_kwh_by_hour
+                .iter()
+                .map(|v| v.max(0.0))
+                .sum::<f32>()
+                / 24.0;
+        }
+        valid.iter().sum::<f32>() / valid.len() as f32
+    }
+
+    /// The `n` lowest-intensity hours, for scheduling runs at nodes with a
+    /// renewable preference.
+    pub fn cleanest_hours(&self, n: usize) -> Vec<u8> {
+        let mut hours: Vec<u8> = (0..24).collect();
+        hours.sort_by(|a, b| {
+            self.kg_co2e_per_kwh_by_hour[*a as usize]
+                .total_cmp(&self.kg_co2e_per_kwh_by_hour[*b as usize])
+        });
+        hours.truncate(n.min(24));
+        hours.sort_unstable();
+        hours
+    }
+}
+
+/// Energy and emissions attributed to one manufacturing batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchEnergyAccount {
+    pub batch_id: String,
+    pub kwh_total: f32,
+    pub kg_co2e_total: f32,
+    pub kg_co2e_per_device: f32,
+    /// Hours the batch ran, as used for the intensity calculation. With a
+    /// renewable preference these are the cleanest hours, not the requested
+    /// ones.
+    pub production_hours: Vec<u8>,
+}
+
+pub struct ManufacturingEnergyAccounting;
+
+impl ManufacturingEnergyAccounting {
+    /// Account a batch run during `production_hours`, with energy spread
+    /// evenly across those hours. When the profile has a renewable
+    /// preference the same number of hours is moved to the grid's cleanest
+    /// hours.
+    pub fn account_batch(
+        batch: &DevicePlasticBatch,
+        energy: &ManufacturingEnergyProfile,
+        grid: &GridIntensityProfile,
+        production_hours: &[u8],
+    ) -> BatchEnergyAccount {
+        let requested: Vec<u8> = production_hours
+            .iter()
+            .copied()
+            .filter(|h| *h < 24)
+            .collect();
+        let hours = if energy.renewable_preference {
+            grid.cleanest_hours(requested.len())
+        } else {
+            requested
+        };
+
+        let kwh_total = batch.shell_feedstock_mass_kg.max(0.0) * energy.target_kwh_per_kg.max(0.0);
+        let kg_co2e_total = kwh_total * grid.mean_intensity(&hours);
+        let kg_co2e_per_device = if batch.estimated_device_count > 0 {
+            kg_co2e_total / batch.estimated_device_count as f32
+        } else {
+            0.0
+        };
+
+        BatchEnergyAccount {
+            batch_id: batch.batch_id.clone(),
+            kwh_total,
+            kg_co2e_total,
+            kg_co2e_per_device,
+            production_hours: hours,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> GridIntensityProfile {
+        let mut kg_co2e_per_kwh_by_hour = [0.5; 24];
+        kg_co2e_per_kwh_by_hour[12] = 0.1;
+        kg_co2e_per_kwh_by_hour[13] = 0.1;
+        GridIntensityProfile {
+            region_code: "TEST".to_string(),
+            kg_co2e_per_kwh_by_hour,
+        }
+    }
+
+    fn energy(renewable_preference: bool) -> ManufacturingEnergyProfile {
+        ManufacturingEnergyProfile {
+            target_kwh_per_kg: 1.0,
+            renewable_preference,
+            load_smoothing_enabled: false,
+        }
+    }
+
+    #[test]
+    fn test_renewable_preference_moves_run_to_cleanest_hours() {
+        let mut batch = DevicePlasticBatch::from_streams("B1".to_string(), &[], 0.25);
+        batch.shell_feedstock_mass_kg = 10.0;
+        batch.estimated_device_count = 40;
+
+        let requested = [20, 21];
+        let plain = ManufacturingEnergyAccounting::account_batch(
+            &batch,
+            &energy(false),
+            &grid(),
+            &requested,
+        );
+        assert_eq!(plain.production_hours, vec![20, 21]);
+        assert!((plain.kg_co2e_total - 5.0).abs() < 1e-4);
+
+        let preferred = ManufacturingEnergyAccounting::account_batch(
+            &batch,
+            &energy(true),
+            &grid(),
+            &requested,
+        );
+        assert_eq!(preferred.production_hours, vec![12, 13]);
+        assert!((preferred.kg_co2e_total - 1.0).abs() < 1e-4);
+        assert_eq!(preferred.kwh_total, plain.kwh_total);
+    }
+}
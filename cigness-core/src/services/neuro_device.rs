@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::carbon_model::CarbonFactors;
 use crate::domain::plastic_recycling::DevicePlasticBatch;
+use crate::services::manufacturing_energy::BatchEnergyAccount;
 
 /// Abstraction for a non-nicotine, non-combustion calm-break device.
 /// This layer is hardware-agnostic and focuses on usage and material impact.
@@ -25,6 +27,18 @@ pub struct NeuroDeviceImpact {
     pub cigarettes_avoided_per_day: f32,
 }
 
+/// Per-device climate balance over a reporting horizon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceNetClimateImpact {
+    pub horizon_days: u32,
+    /// CO₂e from cigarettes avoided by one device's user over the horizon.
+    pub avoided_kg_co2e_per_device: f32,
+    /// Manufacturing CO₂e embodied in one device.
+    pub embodied_kg_co2e_per_device: f32,
+    /// Avoided minus embodied; positive means a net climate benefit.
+    pub net_kg_co2e_per_device: f32,
+}
+
 impl NeuroDeviceModel {
     /// Compute impact from a recycled plastic batch and per-user avoidance factor.
     pub fn compute_impact(
@@ -34,7 +48,7 @@ impl NeuroDeviceModel {
         cigarettes_per_pack: u32,
     ) -> NeuroDeviceImpact {
         let shell_mass_kg = self.shell_mass_kg.max(0.001);
-        let supported_users = (batch.total_input_mass_kg / shell_mass_kg)
+        let supported_users = (batch.shell_feedstock_mass_kg / shell_mass_kg)
             .floor()
             .max(0.0) as u32;
 
@@ -43,8 +57,36 @@ impl NeuroDeviceModel {
 
         NeuroDeviceImpact {
             supported_users,
-            total_recycled_plastic_kg: batch.total_input_mass_kg,
+            total_recycled_plastic_kg: batch.shell_feedstock_mass_kg
+                * batch.recycled_content_ratio.clamp(0.0, 1.0),
             cigarettes_avoided_per_day: cigs_avoided_per_user * supported_users as f32,
         }
     }
+
+    /// Net climate impact of one device: cigarette emissions avoided by its
+    /// user over `horizon_days` minus the batch's manufacturing emissions.
+    pub fn net_climate_impact(
+        &self,
+        impact: &NeuroDeviceImpact,
+        energy: &BatchEnergyAccount,
+        carbon: &CarbonFactors,
+        horizon_days: u32,
+    ) -> DeviceNetClimateImpact {
+        let cigs_avoided_per_device = if impact.supported_users > 0 {
+            impact.cigarettes_avoided_per_day / impact.supported_users as f32
+        } else {
+            0.0
+        };
+        let kg_co2e_per_cigarette = carbon.kg_co2e_per_cigarette + carbon.kg_co2e_per_butt_waste;
+        let avoided_kg_co2e_per_device =
+            cigs_avoided_per_device * kg_co2e_per_cigarette.max(0.0) * horizon_days as f32;
+        let embodied_kg_co2e_per_device = energy.kg_co2e_per_device.max(0.0);
+
+        DeviceNetClimateImpact {
+            horizon_days,
+            avoided_kg_co2e_per_device,
+            embodied_kg_co2e_per_device,
+            net_kg_co2e_per_device: avoided_kg_co2e_per_device - embodied_kg_co2e_per_device,
+        }
+    }
 }
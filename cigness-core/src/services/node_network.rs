@@ -0,0 +1,370 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::services::geo_grid::{GeoGridNode, NodeType};
+use crate::services::node_registry::node_type_for_key;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkRelation {
+    /// Collected material moving from a C-node to an M-node.
+    MaterialFlow,
+    /// Devices moving from an M-node to an R-node.
+    DeviceSupply,
+    /// A P-node's policy zone covering another node.
+    PolicyZone,
+}
+
+impl LinkRelation {
+    /// Relations that carry material or products along the supply chain.
+    pub fn is_supply_chain(&self) -> bool {
+        matches!(
+            self,
+            LinkRelation::MaterialFlow | LinkRelation::DeviceSupply
+        )
+    }
+}
+
+/// Directed link from cigness_node_registry.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeLink {
+    pub from: String,
+    pub to: String,
+    pub relation: LinkRelation,
+}
+
+/// Wire format of cigness_node_registry.json; profiles are not needed here.
+#[derive(Debug, Deserialize)]
+struct NetworkRegistryRoot {
+    nodes: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    links: Vec<NodeLink>,
+}
+
+#[derive(Debug, Error)]
+pub enum NodeNetworkError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unknown node type key in registry: {0}")]
+    UnknownNodeType(String),
+    #[error("Link {from} -> {to} references unknown node {missing}")]
+    UnknownEndpoint {
+        from: String,
+        to: String,
+        missing: String,
+    },
+}
+
+/// Directed graph of grid nodes and their registry links.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeNetwork {
+    pub node_types: BTreeMap<String, NodeType>,
+    pub links: Vec<NodeLink>,
+}
+
+impl NodeNetwork {
+    pub fn load_from_file<P: AsRef<Path>>(registry_path: P) -> Result<Self, NodeNetworkError> {
+        let mut buf = String::new();
+        File::open(registry_path.as_ref())?.read_to_string(&mut buf)?;
+        let root: NetworkRegistryRoot = serde_json::from_str(&buf)?;
+
+        let mut node_types = BTreeMap::new();
+        for (key, ids) in root.nodes {
+            let node_type =
+                node_type_for_key(&key).ok_or(NodeNetworkError::UnknownNodeType(key))?;
+            for id in ids {
+                node_types.insert(id, node_type);
+            }
+        }
+        Self::new(node_types, root.links)
+    }
+
+    /// Build from geo-grid nodes, e.g. imported from KML.
+    pub fn from_nodes(
+        nodes: &[GeoGridNode],
+        links: Vec<NodeLink>,
+    ) -> Result<Self, NodeNetworkError> {
+        let node_types = nodes
+            .iter()
+            .map(|n| (n.node_id.clone(), n.node_type))
+            .collect();
+        Self::new(node_types, links)
+    }
+
+    pub fn new(
+        node_types: BTreeMap<String, NodeType>,
+        links: Vec<NodeLink>,
+    ) -> Result<Self, NodeNetworkError> {
+        for link in &links {
+            for end in [&link.from, &link.to] {
+                if !node_types.contains_key(end) {
+                    return Err(NodeNetworkError::UnknownEndpoint {
+                        from: link.from.clone(),
+                        to: link.to.clone(),
+                        missing: end.clone(),
+                    });
+                }
+            }
+        }
+        Ok(NodeNetwork { node_types, links })
+    }
+
+    pub fn node_type(&self, node_id: &str) -> Option<NodeType> {
+        self.node_types.get(node_id).copied()
+    }
+
+    /// Nodes `node_id` links to along supply-chain relations, sorted.
+    pub fn successors(&self, node_id: &str) -> Vec<&str> {
+        self.neighbours(node_id, |l| (l.from.as_str(), l.to.as_str()))
+    }
+
+    /// Nodes linking to `node_id` along supply-chain relations, sorted.
+    pub fn predecessors(&self, node_id: &str) -> Vec<&str> {
+        self.neighbours(node_id, |l| (l.to.as_str(), l.from.as_str()))
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        node_id: &str,
+        ends: impl Fn(&'a NodeLink) -> (&'a str, &'a str),
+    ) -> Vec<&'a str> {
+        let set: BTreeSet<&str> = self
+            .links
+            .iter()
+            .filter(|l| l.relation.is_supply_chain())
+            .map(ends)
+            .filter(|(start, _)| *start == node_id)
+            .map(|(_, end)| end)
+            .collect();
+        set.into_iter().collect()
+    }
+
+    /// Every maximal supply-chain path starting at `node_id`, e.g.
+    /// C → M → R. Paths stop before revisiting a node.
+    pub fn downstream_paths(&self, node_id: &str) -> Vec<Vec<String>> {
+        let mut paths = Vec::new();
+        self.extend_paths(
+            vec![node_id.to_string()],
+            &|id| self.successors(id),
+            &mut paths,
+        );
+        paths
+    }
+
+    /// Every maximal supply-chain path ending at `node_id`, in flow order.
+    pub fn upstream_paths(&self, node_id: &str) -> Vec<Vec<String>> {
+        let mut paths = Vec::new();
+        self.extend_paths(
+            vec![node_id.to_string()],
+            &|id| self.predecessors(id),
+            &mut paths,
+        );
+        for path in &mut paths {
+            path.reverse();
+        }
+        paths
+    }
+
+    fn extend_paths<'a>(
+        &'a self,
+        path: Vec<String>,
+        next: &dyn Fn(&str) -> Vec<&'a str>,
+        out: &mut Vec<Vec<String>>,
+    ) {
+        let last = path.last().expect("path is never empty");
+        let candidates: Vec<&str> = next(last)
+            .into_iter()
+            .filter(|n| !path.iter().any(|p| p == n))
+            .collect();
+        if candidates.is_empty() {
+            if path.len() > 1 {
+                out.push(path);
+            }
+            return;
+        }
+        for n in candidates {
+            let mut extended = path.clone();
+            extended.push(n.to_string());
+            self.extend_paths(extended, next, out);
+        }
+    }
+
+    /// Nodes reachable from `node_id` along supply-chain links.
+    pub fn downstream_nodes(&self, node_id: &str) -> BTreeSet<String> {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([node_id.to_string()]);
+        while let Some(id) = queue.pop_front() {
+            for n in self.successors(&id) {
+                if n != node_id && seen.insert(n.to_string()) {
+                    queue.push_back(n.to_string());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Nodes with no link of any relation in either direction.
+    pub fn orphan_nodes(&self) -> Vec<&str> {
+        self.node_types
+            .keys()
+            .filter(|id| !self.links.iter().any(|l| &l.from == *id || &l.to == *id))
+            .map(|id| id.as_str())
+            .collect()
+    }
+
+    /// Elementary cycles in the material-flow links, each starting at its
+    /// lexicographically smallest node.
+    pub fn material_flow_cycles(&self) -> Vec<Vec<String>> {
+        let mut adjacency: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for l in self
+            .links
+            .iter()
+            .filter(|l| l.relation == LinkRelation::MaterialFlow)
+        {
+            adjacency.entry(&l.from).or_default().insert(&l.to);
+        }
+
+        let mut cycles = Vec::new();
+        for start in adjacency.keys().copied() {
+            let mut stack = vec![start];
+            Self::find_cycles(&adjacency, start, &mut stack, &mut cycles);
+        }
+        cycles
+    }
+
+    fn find_cycles<'a>(
+        adjacency: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+        start: &'a str,
+        stack: &mut Vec<&'a str>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        let last = *stack.last().expect("stack is never empty");
+        for next in adjacency.get(last).into_iter().flatten().copied() {
+            if next == start {
+                cycles.push(stack.iter().map(|s| s.to_string()).collect());
+            } else if next > start && !stack.contains(&next) {
+                stack.push(next);
+                Self::find_cycles(adjacency, start, stack, cycles);
+                stack.pop();
+            }
+        }
+    }
+
+    /// R-nodes supplied by `m_node_id` that have no other M-node supplying
+    /// devices, i.e. those cut off if it goes offline.
+    pub fn r_nodes_losing_supply(&self, m_node_id: &str) -> Vec<&str> {
+        let supplied: BTreeSet<&str> = self
+            .links
+            .iter()
+            .filter(|l| l.relation == LinkRelation::DeviceSupply && l.from == m_node_id)
+            .map(|l| l.to.as_str())
+            .filter(|to| self.node_type(to) == Some(NodeType::RNode))
+            .collect();
+        supplied
+            .into_iter()
+            .filter(|r| {
+                !self.links.iter().any(|l| {
+                    l.relation == LinkRelation::DeviceSupply
+                        && l.to == *r
+                        && l.from != m_node_id
+                        && self.node_type(&l.from) == Some(NodeType::MNode)
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(from: &str, to: &str, relation: LinkRelation) -> NodeLink {
+        NodeLink {
+            from: from.to_string(),
+            to: to.to_string(),
+            relation,
+        }
+    }
+
+    /// Two material-flow cycles (C1 → M1 → C2 → C1 and C2 ⇄ M2), M1 and M2
+    /// sharing R2, and R3 backed only by M1 plus a non-M-node link.
+    fn fixture() -> NodeNetwork {
+        let node_types = [
+            ("C1", NodeType::CNode),
+            ("C2", NodeType::CNode),
+            ("M1", NodeType::MNode),
+            ("M2", NodeType::MNode),
+            ("R1", NodeType::RNode),
+            ("R2", NodeType::RNode),
+            ("R3", NodeType::RNode),
+            ("P1", NodeType::PNode),
+        ]
+        .iter()
+        .map(|(id, t)| (id.to_string(), *t))
+        .collect();
+        let links = vec![
+            link("C1", "M1", LinkRelation::MaterialFlow),
+            link("M1", "C2", LinkRelation::MaterialFlow),
+            link("C2", "C1", LinkRelation::MaterialFlow),
+            link("C2", "M2", LinkRelation::MaterialFlow),
+            link("M2", "C2", LinkRelation::MaterialFlow),
+            link("M1", "R1", LinkRelation::DeviceSupply),
+            link("M1", "R2", LinkRelation::DeviceSupply),
+            link("M2", "R2", LinkRelation::DeviceSupply),
+            link("M1", "R3", LinkRelation::DeviceSupply),
+            link("C1", "R3", LinkRelation::DeviceSupply),
+            link("P1", "R1", LinkRelation::PolicyZone),
+            link("R1", "M1", LinkRelation::PolicyZone),
+        ];
+        NodeNetwork::new(node_types, links).unwrap()
+    }
+
+    #[test]
+    fn test_material_flow_cycles() {
+        assert_eq!(
+            fixture().material_flow_cycles(),
+            vec![
+                vec!["C1".to_string(), "M1".to_string(), "C2".to_string()],
+                vec!["C2".to_string(), "M2".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_cycles_without_material_flow() {
+        let mut network = fixture();
+        network
+            .links
+            .retain(|l| l.relation != LinkRelation::MaterialFlow);
+        assert!(network.material_flow_cycles().is_empty());
+    }
+
+    #[test]
+    fn test_r_nodes_losing_supply() {
+        let network = fixture();
+        // R2 is also supplied by M2; C1 is not an M-node, so R3 is still cut off.
+        assert_eq!(network.r_nodes_losing_supply("M1"), vec!["R1", "R3"]);
+        assert!(network.r_nodes_losing_supply("M2").is_empty());
+        assert!(network.r_nodes_losing_supply("M9").is_empty());
+    }
+
+    #[test]
+    fn test_unknown_endpoint_is_rejected() {
+        let err = NodeNetwork::new(
+            BTreeMap::from([("C1".to_string(), NodeType::CNode)]),
+            vec![link("C1", "M9", LinkRelation::MaterialFlow)],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            NodeNetworkError::UnknownEndpoint { missing, .. } if missing == "M9"
+        ));
+    }
+}
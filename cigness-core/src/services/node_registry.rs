@@ -0,0 +1,529 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+
+use crate::domain::material_flow::QualityRequirements;
+use crate::services::geo_grid::NodeType;
+use crate::services::manufacturing_energy::ManufacturingEnergyProfile;
+
+/// `location` block shared by all node profiles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeLocation {
+    pub city: String,
+    pub label: String,
+    #[serde(default)]
+    pub linked_kml_tile_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryPrivacy {
+    pub pseudonymous_ids: bool,
+    pub no_advertising_use: bool,
+    pub youth_data_stricter_retention_days: u32,
+    pub adult_data_retention_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeTelemetry {
+    #[serde(default)]
+    pub local_only_fields: Vec<String>,
+    #[serde(default)]
+    pub aggregated_upload_fields: Vec<String>,
+    #[serde(default)]
+    pub privacy: Option<TelemetryPrivacy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeGovernance {
+    pub operator: String,
+    #[serde(default)]
+    pub audit_log_targets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLimits {
+    pub max_sessions_per_day: u32,
+    pub max_minutes_per_session: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XrModeProfile {
+    pub min_age: u8,
+    #[serde(default)]
+    pub max_age: Option<u8>,
+    pub allowed_modules: Vec<String>,
+    pub session_limits: SessionLimits,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XrModes {
+    pub youth: XrModeProfile,
+    pub adult: XrModeProfile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XrModule {
+    pub description: String,
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default)]
+    pub linked_actions: Vec<u32>,
+}
+
+/// Typed view of r_node_xr_profile.cigness.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RNodeProfile {
+    pub r_node_id: String,
+    pub location: NodeLocation,
+    pub modes: XrModes,
+    pub modules: BTreeMap<String, XrModule>,
+    pub telemetry: NodeTelemetry,
+    pub governance: NodeGovernance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptedMaterials {
+    #[serde(default)]
+    pub cigarette_related: Vec<String>,
+    #[serde(default)]
+    pub plastics: Vec<String>,
+    #[serde(default)]
+    pub other: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingCapabilities {
+    pub daily_capacity_kg: f32,
+    #[serde(default)]
+    pub output_streams: Vec<String>,
+}
+
+/// Typed view of c_node_profile.cigness.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CNodeProfile {
+    pub c_node_id: String,
+    pub location: NodeLocation,
+    #[serde(default)]
+    pub role: Vec<String>,
+    pub accepted_materials: AcceptedMaterials,
+    pub processing_capabilities: ProcessingCapabilities,
+    #[serde(default)]
+    pub linked_micro_factories: Vec<String>,
+    pub telemetry: NodeTelemetry,
+    pub governance: NodeGovernance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MNodeInputs {
+    #[serde(default)]
+    pub from_c_nodes: Vec<String>,
+    pub quality_requirements: QualityRequirements,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MNodeOutputs {
+    #[serde(default)]
+    pub products: Vec<String>,
+    /// Units per day keyed by product, e.g. "device_shells".
+    pub daily_capacity: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MNodeEnvironmentalPolicy {
+    pub zero_virgin_plastic: bool,
+    pub scrap_recycling_required: bool,
+    pub emissions_reporting_interval_days: u32,
+}
+
+/// Typed view of m_node_profile.cigness.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MNodeProfile {
+    pub m_node_id: String,
+    pub location: NodeLocation,
+    #[serde(default)]
+    pub role: Vec<String>,
+    pub inputs: MNodeInputs,
+    pub outputs: MNodeOutputs,
+    pub energy_profile: ManufacturingEnergyProfile,
+    pub environmental_policy: MNodeEnvironmentalPolicy,
+    pub telemetry: NodeTelemetry,
+    pub governance: NodeGovernance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetailDensityPolicy {
+    pub max_retailers_per_km2: f32,
+    pub min_distance_between_retailers_m: f32,
+    pub license_cap_enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmokeFreeZones {
+    pub plazas: bool,
+    pub parks: bool,
+    pub transit_stops: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CignessPriority {
+    #[serde(default)]
+    pub preferential_permitting_for: Vec<String>,
+    pub fast_track_review_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PNodePolicies {
+    pub tobacco_retail_density: RetailDensityPolicy,
+    pub smoke_free_zones: SmokeFreeZones,
+    pub cigness_priority: CignessPriority,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PNodeIntegration {
+    pub jurisdiction: String,
+    #[serde(default)]
+    pub reference_documents: Vec<String>,
+}
+
+/// Typed view of p_node_profile.cigness.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PNodeProfile {
+    pub p_node_id: String,
+    pub location: NodeLocation,
+    #[serde(default)]
+    pub role: Vec<String>,
+    pub policies: PNodePolicies,
+    pub integration: PNodeIntegration,
+    pub telemetry: NodeTelemetry,
+    pub governance: NodeGovernance,
+}
+
+/// A profile of any node type. The variant is chosen by which id field the
+/// file carries (`r_node_id`, `c_node_id`, `m_node_id` or `p_node_id`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum NodeProfile {
+    R(RNodeProfile),
+    C(CNodeProfile),
+    M(MNodeProfile),
+    P(PNodeProfile),
+}
+
+impl NodeProfile {
+    pub fn node_id(&self) -> &str {
+        match self {
+            NodeProfile::R(p) => &p.r_node_id,
+            NodeProfile::C(p) => &p.c_node_id,
+            NodeProfile::M(p) => &p.m_node_id,
+            NodeProfile::P(p) => &p.p_node_id,
+        }
+    }
+
+    pub fn node_type(&self) -> NodeType {
+        match self {
+            NodeProfile::R(_) => NodeType::RNode,
+            NodeProfile::C(_) => NodeType::CNode,
+            NodeProfile::M(_) => NodeType::MNode,
+            NodeProfile::P(_) => NodeType::PNode,
+        }
+    }
+
+    pub fn location(&self) -> &NodeLocation {
+        match self {
+            NodeProfile::R(p) => &p.location,
+            NodeProfile::C(p) => &p.location,
+            NodeProfile::M(p) => &p.location,
+            NodeProfile::P(p) => &p.location,
+        }
+    }
+}
+
+/// Id field that marks each profile type.
+const PROFILE_ID_KEYS: [(&str, NodeType); 4] = [
+    ("r_node_id", NodeType::RNode),
+    ("c_node_id", NodeType::CNode),
+    ("m_node_id", NodeType::MNode),
+    ("p_node_id", NodeType::PNode),
+];
+
+impl<'de> Deserialize<'de> for NodeProfile {
+    /// Dispatch on the id key so a malformed profile reports the concrete
+    /// struct's error instead of "did not match any variant".
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let present: Vec<(&str, NodeType)> = PROFILE_ID_KEYS
+            .iter()
+            .filter(|(key, _)| value.get(key).is_some())
+            .copied()
+            .collect();
+        let node_type = match present.as_slice() {
+            [(_, node_type)] => *node_type,
+            [] => {
+                return Err(D::Error::custom(
+                    "no r_node_id, c_node_id, m_node_id or p_node_id field",
+                ))
+            }
+            _ => {
+                let keys: Vec<&str> = present.iter().map(|(key, _)| *key).collect();
+                return Err(D::Error::custom(format!(
+                    "more than one node id field: {}",
+                    keys.join(", ")
+                )));
+            }
+        };
+        let profile = match node_type {
+            NodeType::RNode => serde_json::from_value(value).map(NodeProfile::R),
+            NodeType::CNode => serde_json::from_value(value).map(NodeProfile::C),
+            NodeType::MNode => serde_json::from_value(value).map(NodeProfile::M),
+            NodeType::PNode => serde_json::from_value(value).map(NodeProfile::P),
+        };
+        profile.map_err(D::Error::custom)
+    }
+}
+
+/// Wire format of cigness_node_registry.json.
+#[derive(Debug, Deserialize)]
+struct NodeRegistryRoot {
+    version: String,
+    city: String,
+    /// Node ids keyed by type letter ("R", "C", "M", "P").
+    nodes: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Error)]
+pub enum NodeRegistryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid node profile {}: {source}", path.display())]
+    InvalidProfile {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Unknown node type key in registry: {0}")]
+    UnknownNodeType(String),
+    #[error("Node {0} is listed more than once")]
+    DuplicateNode(String),
+    #[error("More than one profile for node {0}")]
+    DuplicateProfile(String),
+    #[error("Profile for {node_id} is {profile:?} but the registry lists {registry:?}")]
+    TypeMismatch {
+        node_id: String,
+        registry: NodeType,
+        profile: NodeType,
+    },
+    #[error("Profiles for nodes not in the registry: {0:?}")]
+    UnknownProfiles(Vec<String>),
+    #[error("Registry nodes without a profile: {0:?}")]
+    MissingProfiles(Vec<String>),
+}
+
+/// A registry node joined with its profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredNode {
+    pub node_id: String,
+    pub node_type: NodeType,
+    pub profile: NodeProfile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeRegistry {
+    pub version: String,
+    pub city: String,
+    /// Nodes in registry order (R, C, M, P groups as listed).
+    pub nodes: Vec<RegisteredNode>,
+}
+
+impl NodeRegistry {
+    /// Load the registry and join every node with exactly one profile from
+    /// `profile_paths`. Profiles for unlisted nodes and listed nodes without
+    /// a profile are both errors.
+    pub fn load_with_profiles<P: AsRef<Path>>(
+        registry_path: P,
+        profile_paths: &[P],
+    ) -> Result<Self, NodeRegistryError> {
+        let root: NodeRegistryRoot = read_json(registry_path.as_ref())?;
+        Self::join(root, read_profiles(profile_paths)?)
+    }
+
+    /// Like `load_with_profiles`, but listed nodes without a profile are
+    /// left out of the registry and returned by id, in registry order,
+    /// instead of failing the load.
+    pub fn load_with_available_profiles<P: AsRef<Path>>(
+        registry_path: P,
+        profile_paths: &[P],
+    ) -> Result<(Self, Vec<String>), NodeRegistryError> {
+        let root: NodeRegistryRoot = read_json(registry_path.as_ref())?;
+        Self::join_available(root, read_profiles(profile_paths)?)
+    }
+
+    fn join(root: NodeRegistryRoot, profiles: Vec<NodeProfile>) -> Result<Self, NodeRegistryError> {
+        let (registry, missing) = Self::join_available(root, profiles)?;
+        if !missing.is_empty() {
+            return Err(NodeRegistryError::MissingProfiles(missing));
+        }
+        Ok(registry)
+    }
+
+    fn join_available(
+        root: NodeRegistryRoot,
+        profiles: Vec<NodeProfile>,
+    ) -> Result<(Self, Vec<String>), NodeRegistryError> {
+        let mut listed: Vec<(String, NodeType)> = Vec::new();
+        for (key, ids) in &root.nodes {
+            let node_type = node_type_for_key(key)
+                .ok_or_else(|| NodeRegistryError::UnknownNodeType(key.clone()))?;
+            for id in ids {
+                if listed.iter().any(|(l, _)| l == id) {
+                    return Err(NodeRegistryError::DuplicateNode(id.clone()));
+                }
+                listed.push((id.clone(), node_type));
+            }
+        }
+        // BTreeMap iteration is alphabetical; restore the R, C, M, P order.
+        listed.sort_by_key(|(_, t)| type_order(*t));
+
+        let mut by_id: HashMap<String, NodeProfile> = HashMap::new();
+        for profile in profiles {
+            let id = profile.node_id().to_string();
+            if by_id.insert(id.clone(), profile).is_some() {
+                return Err(NodeRegistryError::DuplicateProfile(id));
+            }
+        }
+
+        let mut unknown: Vec<String> = by_id
+            .keys()
+            .filter(|id| !listed.iter().any(|(l, _)| l == *id))
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(NodeRegistryError::UnknownProfiles(unknown));
+        }
+
+        let mut nodes = Vec::with_capacity(listed.len());
+        let mut missing_profiles = Vec::new();
+        for (node_id, node_type) in listed {
+            let Some(profile) = by_id.remove(&node_id) else {
+                missing_profiles.push(node_id);
+                continue;
+            };
+            if profile.node_type() != node_type {
+                return Err(NodeRegistryError::TypeMismatch {
+                    node_id,
+                    registry: node_type,
+                    profile: profile.node_type(),
+                });
+            }
+            nodes.push(RegisteredNode {
+                node_id,
+                node_type,
+                profile,
+            });
+        }
+
+        let registry = NodeRegistry {
+            version: root.version,
+            city: root.city,
+            nodes,
+        };
+        Ok((registry, missing_profiles))
+    }
+
+    pub fn get(&self, node_id: &str) -> Option<&RegisteredNode> {
+        self.nodes.iter().find(|n| n.node_id == node_id)
+    }
+}
+
+/// Node type for a registry group key ("R", "C", "M", "P").
+pub fn node_type_for_key(key: &str) -> Option<NodeType> {
+    match key {
+        "R" => Some(NodeType::RNode),
+        "C" => Some(NodeType::CNode),
+        "M" => Some(NodeType::MNode),
+        "P" => Some(NodeType::PNode),
+        _ => None,
+    }
+}
+
+fn type_order(node_type: NodeType) -> u8 {
+    match node_type {
+        NodeType::RNode => 0,
+        NodeType::CNode => 1,
+        NodeType::MNode => 2,
+        NodeType::PNode => 3,
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, NodeRegistryError> {
+    let mut buf = String::new();
+    File::open(path)?.read_to_string(&mut buf)?;
+    Ok(serde_json::from_str(&buf)?)
+}
+
+fn read_profiles<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<NodeProfile>, NodeRegistryError> {
+    let mut profiles = Vec::with_capacity(paths.len());
+    for path in paths {
+        let path = path.as_ref();
+        let mut buf = String::new();
+        File::open(path)?.read_to_string(&mut buf)?;
+        let profile = serde_json::from_str::<NodeProfile>(&buf).map_err(|source| {
+            NodeRegistryError::InvalidProfile {
+                path: path.to_path_buf(),
+                source,
+            }
+        })?;
+        profiles.push(profile);
+    }
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"{
+        "version": "1.0.0",
+        "city": "Phoenix",
+        "nodes": { "C": ["C_Node_001"], "P": ["P_Node_001", "P_Node_002"] }
+    }"#;
+
+    fn profiles() -> Vec<NodeProfile> {
+        vec![
+            serde_json::from_str(include_str!("../../../c_node_profile.cigness.json")).unwrap(),
+            serde_json::from_str(include_str!("../../../p_node_profile.cigness.json")).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_node_without_profile_is_error() {
+        let root: NodeRegistryRoot = serde_json::from_str(REGISTRY).unwrap();
+        match NodeRegistry::join(root, profiles()) {
+            Err(NodeRegistryError::MissingProfiles(ids)) => assert_eq!(ids, vec!["P_Node_002"]),
+            other => panic!("expected MissingProfiles, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_available_profiles_join_reports_missing() {
+        let root: NodeRegistryRoot = serde_json::from_str(REGISTRY).unwrap();
+        let (registry, missing) = NodeRegistry::join_available(root, profiles()).unwrap();
+        let ids: Vec<&str> = registry.nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(ids, vec!["C_Node_001", "P_Node_001"]);
+        assert_eq!(missing, vec!["P_Node_002"]);
+    }
+
+    #[test]
+    fn test_profile_without_id_key_is_error() {
+        let err = serde_json::from_str::<NodeProfile>(r#"{"location": {}}"#).unwrap_err();
+        assert!(err.to_string().contains("r_node_id"));
+    }
+}
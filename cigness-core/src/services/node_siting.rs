@@ -0,0 +1,416 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::services::geo_grid::{haversine_km, GeoGridNode, NodeType};
+use crate::services::node_registry::{PNodePolicies, PNodeProfile};
+
+/// Kind of location a candidate site sits in, matched against the P-node
+/// `smoke_free_zones` flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SiteSetting {
+    Plaza,
+    Park,
+    TransitStop,
+    Street,
+    Retail,
+    Campus,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateSite {
+    pub site_id: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub setting: SiteSetting,
+    /// Distance to the nearest licensed tobacco retailer (m), if surveyed.
+    #[serde(default)]
+    pub nearest_tobacco_retailer_m: Option<f32>,
+}
+
+/// Demand location weighted by smoker visits per day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemandPoint {
+    pub point_id: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub weight: f32,
+}
+
+impl DemandPoint {
+    /// One demand point per grid node, weighted by its smoker visits.
+    pub fn from_nodes(nodes: &[GeoGridNode]) -> Vec<DemandPoint> {
+        nodes
+            .iter()
+            .filter(|n| n.avg_smoker_visits_per_day > 0)
+            .map(|n| DemandPoint {
+                point_id: n.node_id.clone(),
+                latitude: n.latitude,
+                longitude: n.longitude,
+                weight: n.avg_smoker_visits_per_day as f32,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SitingError {
+    #[error("Grid node {node_id} is {node_type:?}, not a P-node")]
+    NotAPNode {
+        node_id: String,
+        node_type: NodeType,
+    },
+    #[error("Grid node {node_id} does not match profile {p_node_id}")]
+    ProfileMismatch { node_id: String, p_node_id: String },
+}
+
+/// Area around a P-node where its policies apply: smoke-free settings and
+/// the retailer spacing from `tobacco_retail_density` both restrict siting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyZone {
+    pub p_node_id: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_km: f64,
+    pub policies: PNodePolicies,
+}
+
+impl PolicyZone {
+    /// Zone centred on `node`, which must be the grid node `profile`
+    /// describes.
+    pub fn from_profile(
+        node: &GeoGridNode,
+        profile: &PNodeProfile,
+        radius_km: f64,
+    ) -> Result<Self, SitingError> {
+        if node.node_type != NodeType::PNode {
+            return Err(SitingError::NotAPNode {
+                node_id: node.node_id.clone(),
+                node_type: node.node_type,
+            });
+        }
+        if node.node_id != profile.p_node_id {
+            return Err(SitingError::ProfileMismatch {
+                node_id: node.node_id.clone(),
+                p_node_id: profile.p_node_id.clone(),
+            });
+        }
+        Ok(PolicyZone {
+            p_node_id: profile.p_node_id.clone(),
+            latitude: node.latitude,
+            longitude: node.longitude,
+            radius_km,
+            policies: profile.policies.clone(),
+        })
+    }
+
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        haversine_km(self.latitude, self.longitude, latitude, longitude) <= self.radius_km
+    }
+
+    /// Why this zone rules out `site`, if it does. Sites in an enabled
+    /// smoke-free setting would draw smokers into it; sites closer to a
+    /// tobacco retailer than `min_distance_between_retailers_m` would sit
+    /// beside tobacco sales. Sites without a retailer survey pass the
+    /// spacing check.
+    pub fn exclusion_reason(&self, site: &CandidateSite) -> Option<String> {
+        if !self.contains(site.latitude, site.longitude) {
+            return None;
+        }
+        let zones = &self.policies.smoke_free_zones;
+        let smoke_free = match site.setting {
+            SiteSetting::Plaza => zones.plazas,
+            SiteSetting::Park => zones.parks,
+            SiteSetting::TransitStop => zones.transit_stops,
+            _ => false,
+        };
+        if smoke_free {
+            return Some(format!(
+                "{:?} is a smoke-free zone under {}",
+                site.setting, self.p_node_id
+            ));
+        }
+        let min_m = self
+            .policies
+            .tobacco_retail_density
+            .min_distance_between_retailers_m;
+        match site.nearest_tobacco_retailer_m {
+            Some(d) if d < min_m => Some(format!(
+                "{:.0} m from a tobacco retailer, below the {:.0} m spacing under {}",
+                d, min_m, self.p_node_id
+            )),
+            _ => None,
+        }
+    }
+
+    /// Permit review time for a `node_type` site here, if the zone lists
+    /// that type under `preferential_permitting_for` (e.g. "R_Nodes").
+    pub fn fast_track_review_days(&self, site: &CandidateSite, node_type: NodeType) -> Option<u32> {
+        if !self.contains(site.latitude, site.longitude) {
+            return None;
+        }
+        let key = match node_type {
+            NodeType::RNode => "R_Nodes",
+            NodeType::CNode => "C_Nodes",
+            NodeType::MNode => "M_Nodes",
+            NodeType::PNode => "P_Nodes",
+        };
+        let priority = &self.policies.cigness_priority;
+        priority
+            .preferential_permitting_for
+            .iter()
+            .any(|p| p == key)
+            .then_some(priority.fast_track_review_days)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitingRequest {
+    pub node_type: NodeType,
+    /// Number of new nodes to place.
+    pub budget: usize,
+    /// Demand within this distance of a node counts as covered.
+    pub travel_radius_km: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedSite {
+    /// 1-based order of selection.
+    pub rank: usize,
+    pub site_id: String,
+    /// Newly covered demand weight when this site was added.
+    pub marginal_gain: f32,
+    pub cumulative_covered: f32,
+    /// Shortest fast-track permit review among zones giving this node type
+    /// preferential permitting at the site.
+    pub fast_track_review_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedSite {
+    pub site_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SitingResult {
+    pub selected: Vec<RankedSite>,
+    pub excluded: Vec<ExcludedSite>,
+    pub total_demand: f32,
+    /// Demand already covered by existing nodes of the requested type.
+    pub covered_before: f32,
+    pub covered_after: f32,
+}
+
+pub struct NodeSitingOptimizer;
+
+impl NodeSitingOptimizer {
+    /// Greedy max-coverage: repeatedly add the permitted site that covers
+    /// the most not-yet-covered demand, until the budget is spent or no
+    /// site adds coverage. Demand near existing nodes of the same type is
+    /// covered from the start. Ties go to the lower site id.
+    pub fn optimize(
+        request: &SitingRequest,
+        candidates: &[CandidateSite],
+        demand: &[DemandPoint],
+        existing: &[GeoGridNode],
+        zones: &[PolicyZone],
+    ) -> SitingResult {
+        let radius = request.travel_radius_km;
+        let weights: Vec<f32> = demand.iter().map(|d| d.weight.max(0.0)).collect();
+        let total_demand: f32 = weights.iter().sum();
+
+        let mut covered: Vec<bool> = demand
+            .iter()
+            .map(|d| {
+                existing
+                    .iter()
+                    .filter(|n| n.node_type == request.node_type)
+                    .any(|n| n.distance_km_to(d.latitude, d.longitude) <= radius)
+            })
+            .collect();
+        let covered_before: f32 = Self::covered_weight(&covered, &weights);
+
+        let mut excluded = Vec::new();
+        let mut feasible: Vec<(&CandidateSite, Vec<usize>)> = Vec::new();
+        for site in candidates {
+            if let Some(reason) = zones.iter().find_map(|z| z.exclusion_reason(site)) {
+                excluded.push(ExcludedSite {
+                    site_id: site.site_id.clone(),
+                    reason,
+                });
+                continue;
+            }
+            let reach = demand
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| {
+                    haversine_km(site.latitude, site.longitude, d.latitude, d.longitude) <= radius
+                })
+                .map(|(i, _)| i)
+                .collect();
+            feasible.push((site, reach));
+        }
+        feasible.sort_by(|a, b| a.0.site_id.cmp(&b.0.site_id));
+
+        let mut selected = Vec::new();
+        let mut cumulative = covered_before;
+        while selected.len() < request.budget {
+            let best = feasible
+                .iter()
+                .enumerate()
+                .map(|(i, (_, reach))| {
+                    let gain: f32 = reach
+                        .iter()
+                        .filter(|d| !covered[**d])
+                        .map(|d| weights[*d])
+                        .sum();
+                    (i, gain)
+                })
+                .fold(None, |best: Option<(usize, f32)>, (i, gain)| match best {
+                    Some((_, g)) if g >= gain => best,
+                    _ => Some((i, gain)),
+                });
+            let Some((idx, gain)) = best else {
+                break;
+            };
+            if gain <= 0.0 {
+                break;
+            }
+
+            let (site, reach) = feasible.remove(idx);
+            for d in reach {
+                covered[d] = true;
+            }
+            cumulative += gain;
+            selected.push(RankedSite {
+                rank: selected.len() + 1,
+                site_id: site.site_id.clone(),
+                marginal_gain: gain,
+                cumulative_covered: cumulative,
+                fast_track_review_days: zones
+                    .iter()
+                    .filter_map(|z| z.fast_track_review_days(site, request.node_type))
+                    .min(),
+            });
+        }
+
+        SitingResult {
+            selected,
+            excluded,
+            total_demand,
+            covered_before,
+            covered_after: Self::covered_weight(&covered, &weights),
+        }
+    }
+
+    fn covered_weight(covered: &[bool], weights: &[f32]) -> f32 {
+        covered
+            .iter()
+            .zip(weights)
+            .filter(|(c, _)| **c)
+            .map(|(_, w)| *w)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::node_registry::{CignessPriority, RetailDensityPolicy, SmokeFreeZones};
+
+    fn zone() -> PolicyZone {
+        PolicyZone {
+            p_node_id: "P_Node_001".to_string(),
+            latitude: 33.4484,
+            longitude: -112.0740,
+            radius_km: 1.0,
+            policies: PNodePolicies {
+                tobacco_retail_density: RetailDensityPolicy {
+                    max_retailers_per_km2: 3.0,
+                    min_distance_between_retailers_m: 200.0,
+                    license_cap_enabled: true,
+                },
+                smoke_free_zones: SmokeFreeZones {
+                    plazas: true,
+                    parks: false,
+                    transit_stops: true,
+                },
+                cigness_priority: CignessPriority {
+                    preferential_permitting_for: vec!["R_Nodes".to_string()],
+                    fast_track_review_days: 15,
+                },
+            },
+        }
+    }
+
+    fn site(id: &str, setting: SiteSetting, retailer_m: Option<f32>) -> CandidateSite {
+        CandidateSite {
+            site_id: id.to_string(),
+            latitude: 33.4490,
+            longitude: -112.0745,
+            setting,
+            nearest_tobacco_retailer_m: retailer_m,
+        }
+    }
+
+    #[test]
+    fn test_smoke_free_setting_is_excluded() {
+        let reason = zone()
+            .exclusion_reason(&site("plaza", SiteSetting::Plaza, None))
+            .unwrap();
+        assert!(reason.contains("smoke-free"));
+        // Parks are not smoke-free under this zone.
+        assert!(zone()
+            .exclusion_reason(&site("park", SiteSetting::Park, None))
+            .is_none());
+    }
+
+    #[test]
+    fn test_site_near_retailer_is_excluded() {
+        let z = zone();
+        let reason = z
+            .exclusion_reason(&site("near", SiteSetting::Street, Some(50.0)))
+            .unwrap();
+        assert!(reason.contains("tobacco retailer"));
+        assert!(z
+            .exclusion_reason(&site("far", SiteSetting::Street, Some(250.0)))
+            .is_none());
+        assert!(z
+            .exclusion_reason(&site("unsurveyed", SiteSetting::Street, None))
+            .is_none());
+    }
+
+    #[test]
+    fn test_rules_apply_only_inside_zone() {
+        let mut outside = site("outside", SiteSetting::Plaza, Some(10.0));
+        outside.latitude = 33.60;
+        assert!(zone().exclusion_reason(&outside).is_none());
+    }
+
+    #[test]
+    fn test_optimize_reports_each_exclusion() {
+        let demand = vec![DemandPoint {
+            point_id: "d".to_string(),
+            latitude: 33.4490,
+            longitude: -112.0745,
+            weight: 10.0,
+        }];
+        let candidates = vec![
+            site("near", SiteSetting::Street, Some(50.0)),
+            site("ok", SiteSetting::Street, Some(500.0)),
+            site("plaza", SiteSetting::Plaza, None),
+        ];
+        let request = SitingRequest {
+            node_type: NodeType::RNode,
+            budget: 3,
+            travel_radius_km: 1.0,
+        };
+        let result = NodeSitingOptimizer::optimize(&request, &candidates, &demand, &[], &[zone()]);
+        assert_eq!(result.selected.len(), 1);
+        assert_eq!(result.selected[0].site_id, "ok");
+        assert_eq!(result.selected[0].fast_track_review_days, Some(15));
+        let excluded: Vec<&str> = result.excluded.iter().map(|e| e.site_id.as_str()).collect();
+        assert_eq!(excluded, vec!["near", "plaza"]);
+    }
+}
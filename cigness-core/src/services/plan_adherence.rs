@@ -0,0 +1,358 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::services::cessation_plan::CessationPlan;
+use crate::services::tapering_schedule::TaperingSchedule;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CravingLevel {
+    None,
+    Mild,
+    Moderate,
+    Severe,
+}
+
+/// Coarse note category; free-text notes stay with the counselor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckInNoteCategory {
+    Routine,
+    StressTrigger,
+    SocialTrigger,
+    WithdrawalSymptoms,
+    Slip,
+    Other,
+}
+
+/// A monitoring check-in held for a cessation plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckIn {
+    pub profile_id: String,
+    pub date: NaiveDate,
+    pub reported_packs_per_day: f32,
+    pub craving_level: CravingLevel,
+    pub notes_category: CheckInNoteCategory,
+}
+
+/// Rules for classifying adherence and raising a referral flag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdherenceRules {
+    /// A check-in within this many days of a planned date counts for it.
+    pub grace_days: i64,
+    /// Reported above target by more than this fraction of baseline is off track.
+    pub off_track_tolerance: f32,
+    /// Missed check-ins at or above this count make the plan at risk.
+    pub at_risk_missed_check_ins: usize,
+    /// Consecutive off-track check-ins at or above this count make the plan at risk.
+    pub at_risk_consecutive_off_track: usize,
+    /// Treat a severe craving report at the latest check-in as at risk.
+    pub severe_craving_is_at_risk: bool,
+    /// Raise a referral flag whenever the status is at risk.
+    pub refer_when_at_risk: bool,
+}
+
+impl Default for AdherenceRules {
+    fn default() -> Self {
+        AdherenceRules {
+            grace_days: 2,
+            off_track_tolerance: 0.10,
+            at_risk_missed_check_ins: 2,
+            at_risk_consecutive_off_track: 2,
+            severe_craving_is_at_risk: true,
+            refer_when_at_risk: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdherenceStatus {
+    OnTrack,
+    OffTrack,
+    AtRisk,
+}
+
+/// Reported consumption against the schedule at one check-in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckInComparison {
+    pub date: NaiveDate,
+    pub reported_packs_per_day: f32,
+    pub target_packs_per_day: f32,
+    pub on_target: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdherenceReport {
+    pub profile_id: String,
+    pub scheduled_check_ins: usize,
+    pub attended_check_ins: usize,
+    /// Planned dates up to `as_of` with no check-in within the grace window.
+    pub missed_dates: Vec<NaiveDate>,
+    pub comparisons: Vec<CheckInComparison>,
+    pub status: AdherenceStatus,
+    /// New referral needed; false when the plan already recommends one.
+    pub referral_flag: bool,
+    pub referral_reasons: Vec<String>,
+}
+
+pub struct AdherenceTracker;
+
+impl AdherenceTracker {
+    /// Evaluate check-ins for `plan` up to and including `as_of`. Check-ins
+    /// for other profiles are ignored.
+    pub fn evaluate(
+        plan: &CessationPlan,
+        schedule: &TaperingSchedule,
+        check_ins: &[CheckIn],
+        rules: &AdherenceRules,
+        as_of: NaiveDate,
+    ) -> AdherenceReport {
+        let mut own: Vec<&CheckIn> = check_ins
+            .iter()
+            .filter(|c| c.profile_id == plan.profile_id && c.date <= as_of)
+            .collect();
+        own.sort_by_key(|c| c.date);
+
+        // Only planned dates whose grace window has closed can be missed.
+        let due: Vec<NaiveDate> = schedule
+            .check_in_dates
+            .iter()
+            .copied()
+            .filter(|d| *d + chrono::Duration::days(rules.grace_days) <= as_of)
+            .collect();
+        let missed_dates: Vec<NaiveDate> = due
+            .iter()
+            .copied()
+            .filter(|d| {
+                !own.iter()
+                    .any(|c| (c.date - *d).num_days().abs() <= rules.grace_days)
+            })
+            .collect();
+
+        let tolerance = rules.off_track_tolerance * plan.baseline_packs_per_day.max(0.05);
+        let comparisons: Vec<CheckInComparison> = own
+            .iter()
+            .filter_map(|c| {
+                let target = schedule.target_on(c.date)?;
+                Some(CheckInComparison {
+                    date: c.date,
+                    reported_packs_per_day: c.reported_packs_per_day,
+                    target_packs_per_day: target,
+                    on_target: c.reported_packs_per_day - target <= tolerance,
+                })
+            })
+            .collect();
+
+        let consecutive_off_track = comparisons
+            .iter()
+            .rev()
+            .take_while(|c| !c.on_target)
+            .count();
+        let latest_off_track = comparisons.last().is_some_and(|c| !c.on_target);
+        let latest_severe = own
+            .last()
+            .is_some_and(|c| c.craving_level == CravingLevel::Severe);
+
+        let mut risk_reasons = Vec::new();
+        if missed_dates.len() >= rules.at_risk_missed_check_ins {
+            risk_reasons.push(format!("{} planned check-ins missed", missed_dates.len()));
+        }
+        if consecutive_off_track >= rules.at_risk_consecutive_off_track {
+            risk_reasons.push(format!(
+                "{} consecutive check-ins above target",
+                consecutive_off_track
+            ));
+        }
+        if rules.severe_craving_is_at_risk && latest_severe {
+            risk_reasons.push("severe craving reported at latest check-in".to_string());
+        }
+
+        let status = if !risk_reasons.is_empty() {
+            AdherenceStatus::AtRisk
+        } else if latest_off_track || !missed_dates.is_empty() {
+            AdherenceStatus::OffTrack
+        } else {
+            AdherenceStatus::OnTrack
+        };
+
+        let referral_flag = rules.refer_when_at_risk
+            && status == AdherenceStatus::AtRisk
+            && !plan.referral_recommended;
+
+        AdherenceReport {
+            profile_id: plan.profile_id.clone(),
+            scheduled_check_ins: due.len(),
+            attended_check_ins: due.len() - missed_dates.len(),
+            missed_dates,
+            comparisons,
+            status,
+            referral_flag,
+            referral_reasons: if referral_flag {
+                risk_reasons
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::tapering_schedule::{TaperShape, WeeklyTarget};
+    use chrono::Duration;
+
+    fn start() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+    }
+
+    fn day(offset: i64) -> NaiveDate {
+        start() + Duration::days(offset)
+    }
+
+    fn plan(referral_recommended: bool) -> CessationPlan {
+        CessationPlan {
+            profile_id: "p-1".to_string(),
+            baseline_packs_per_day: 2.0,
+            target_packs_per_day_90d: 0.5,
+            referral_recommended,
+            monitoring_interval_days: 7,
+        }
+    }
+
+    /// Weekly targets 2.0, 1.5, 1.0, 0.5 with check-ins at the end of each week.
+    fn schedule() -> TaperingSchedule {
+        TaperingSchedule {
+            profile_id: "p-1".to_string(),
+            shape: TaperShape::Linear,
+            start_date: start(),
+            quit_date: None,
+            weekly_targets: [2.0, 1.5, 1.0, 0.5]
+                .iter()
+                .enumerate()
+                .map(|(i, t)| WeeklyTarget {
+                    week: i as u32 + 1,
+                    week_start: day(i as i64 * 7),
+                    target_packs_per_day: *t,
+                })
+                .collect(),
+            check_in_dates: vec![day(7), day(14), day(21), day(28)],
+        }
+    }
+
+    /// Off-track tolerance of 0.25 × baseline 2.0 = 0.5 packs/day.
+    fn rules() -> AdherenceRules {
+        AdherenceRules {
+            off_track_tolerance: 0.25,
+            ..Default::default()
+        }
+    }
+
+    fn check_in(date: NaiveDate, reported_packs_per_day: f32) -> CheckIn {
+        CheckIn {
+            profile_id: "p-1".to_string(),
+            date,
+            reported_packs_per_day,
+            craving_level: CravingLevel::Mild,
+            notes_category: CheckInNoteCategory::Routine,
+        }
+    }
+
+    fn evaluate(plan: &CessationPlan, check_ins: &[CheckIn], as_of: NaiveDate) -> AdherenceReport {
+        AdherenceTracker::evaluate(plan, &schedule(), check_ins, &rules(), as_of)
+    }
+
+    #[test]
+    fn test_on_schedule_is_on_track() {
+        let check_ins = [check_in(day(7), 1.5), check_in(day(14), 1.0)];
+        let report = evaluate(&plan(false), &check_ins, day(16));
+        assert_eq!(report.status, AdherenceStatus::OnTrack);
+        assert_eq!(report.scheduled_check_ins, 2);
+        assert_eq!(report.attended_check_ins, 2);
+        assert!(!report.referral_flag);
+    }
+
+    #[test]
+    fn test_report_at_tolerance_is_on_target() {
+        // Week 3 target 1.0; 1.5 is exactly target + tolerance.
+        let report = evaluate(&plan(false), &[check_in(day(14), 1.5)], day(14));
+        assert!(report.comparisons[0].on_target);
+        let report = evaluate(&plan(false), &[check_in(day(14), 1.6)], day(14));
+        assert!(!report.comparisons[0].on_target);
+        assert_eq!(report.status, AdherenceStatus::OffTrack);
+    }
+
+    #[test]
+    fn test_grace_window() {
+        // Two days late still counts for the day-7 check-in.
+        let report = evaluate(&plan(false), &[check_in(day(9), 1.0)], day(9));
+        assert_eq!(report.scheduled_check_ins, 1);
+        assert!(report.missed_dates.is_empty());
+
+        // Day 14's window is still open on day 15, so it is not yet missed.
+        let report = evaluate(&plan(false), &[check_in(day(7), 1.5)], day(15));
+        assert_eq!(report.scheduled_check_ins, 1);
+        assert!(report.missed_dates.is_empty());
+
+        // Three days late misses the planned date.
+        let report = evaluate(&plan(false), &[check_in(day(10), 1.0)], day(10));
+        assert_eq!(report.missed_dates, vec![day(7)]);
+        assert_eq!(report.status, AdherenceStatus::OffTrack);
+    }
+
+    #[test]
+    fn test_missed_check_ins_are_at_risk() {
+        let report = evaluate(&plan(false), &[], day(16));
+        assert_eq!(report.missed_dates, vec![day(7), day(14)]);
+        assert_eq!(report.attended_check_ins, 0);
+        assert_eq!(report.status, AdherenceStatus::AtRisk);
+        assert!(report.referral_flag);
+    }
+
+    #[test]
+    fn test_consecutive_off_track_is_at_risk() {
+        let check_ins = [check_in(day(7), 2.5), check_in(day(14), 2.0)];
+        let report = evaluate(&plan(false), &check_ins, day(14));
+        assert_eq!(report.status, AdherenceStatus::AtRisk);
+        assert!(report.referral_flag);
+        assert_eq!(report.referral_reasons.len(), 1);
+
+        // A plan that already recommends referral does not raise a new flag.
+        let report = evaluate(&plan(true), &check_ins, day(14));
+        assert_eq!(report.status, AdherenceStatus::AtRisk);
+        assert!(!report.referral_flag);
+        assert!(report.referral_reasons.is_empty());
+    }
+
+    #[test]
+    fn test_severe_craving_at_latest_check_in() {
+        let mut severe = check_in(day(7), 1.5);
+        severe.craving_level = CravingLevel::Severe;
+        let report = evaluate(&plan(false), std::slice::from_ref(&severe), day(7));
+        assert_eq!(report.status, AdherenceStatus::AtRisk);
+
+        let rules = AdherenceRules {
+            severe_craving_is_at_risk: false,
+            ..rules()
+        };
+        let report =
+            AdherenceTracker::evaluate(&plan(false), &schedule(), &[severe], &rules, day(7));
+        assert_eq!(report.status, AdherenceStatus::OnTrack);
+    }
+
+    #[test]
+    fn test_other_profiles_and_future_check_ins_are_ignored() {
+        let mut other = check_in(day(7), 5.0);
+        other.profile_id = "p-2".to_string();
+        let check_ins = [other, check_in(day(7), 1.5), check_in(day(20), 5.0)];
+        let report = evaluate(&plan(false), &check_ins, day(9));
+        assert_eq!(report.comparisons.len(), 1);
+        assert_eq!(report.status, AdherenceStatus::OnTrack);
+    }
+
+    #[test]
+    fn test_check_in_before_start_has_no_comparison() {
+        let report = evaluate(&plan(false), &[check_in(day(-1), 3.0)], day(0));
+        assert!(report.comparisons.is_empty());
+        assert_eq!(report.scheduled_check_ins, 0);
+        assert_eq!(report.status, AdherenceStatus::OnTrack);
+    }
+}
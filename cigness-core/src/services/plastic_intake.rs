@@ -0,0 +1,203 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::plastic_recycling::{PlasticStream, PlasticType};
+
+/// Wire format of `material_sources` in cigness.plastic-loop.json.
+#[derive(Debug, Deserialize)]
+struct PlasticLoopRoot {
+    material_sources: Vec<MaterialSource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaterialSource {
+    plastic_type: String,
+    accepted_from: Vec<String>,
+    preferred_streams: Vec<String>,
+}
+
+/// Wire format of `accepted_materials` in c_node_profile.cigness.json.
+#[derive(Debug, Deserialize)]
+struct CNodeProfileRoot {
+    c_node_id: String,
+    accepted_materials: AcceptedMaterials,
+}
+
+#[derive(Debug, Deserialize)]
+struct AcceptedMaterials {
+    plastics: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum IntakeConfigError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unknown resin code in config: {0}")]
+    UnknownResin(String),
+}
+
+/// Loop rules for one resin from the plastic-loop config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResinAcceptance {
+    pub plastic_type: PlasticType,
+    pub accepted_from: Vec<String>,
+    pub preferred_streams: Vec<String>,
+}
+
+/// Intake outcome for a single stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IntakeDecision {
+    /// Stream can enter the device plastic loop.
+    Accepted,
+    /// Stream is held for manual review and kept out of batches.
+    Quarantined { reasons: Vec<String> },
+    /// Stream cannot be taken in at this node.
+    Rejected { reasons: Vec<String> },
+}
+
+/// Stream intake rules joined from the C-node profile (what the node
+/// physically accepts) and the plastic-loop config (what qualifies for the
+/// device loop).
+///
+/// A resin the node does not accept, or a reported source category the node
+/// does not list for that resin, is rejected. A resin the node accepts
+/// but the loop does not, a source category outside `accepted_from`, or a
+/// stream type outside `preferred_streams` is quarantined. Streams missing
+/// category or stream type are quarantined as unverified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlasticIntakePolicy {
+    pub c_node_id: String,
+    /// Resins the C-node takes in, with the product category from the
+    /// profile entry (e.g. "PET_bottles" → PET, "bottles").
+    pub node_accepted: Vec<(PlasticType, String)>,
+    pub loop_resins: Vec<ResinAcceptance>,
+}
+
+impl PlasticIntakePolicy {
+    pub fn load_from_files<P: AsRef<Path>>(
+        plastic_loop_path: P,
+        c_node_profile_path: P,
+    ) -> Result<Self, IntakeConfigError> {
+        let plastic_root: PlasticLoopRoot = read_json(plastic_loop_path.as_ref())?;
+        let node_root: CNodeProfileRoot = read_json(c_node_profile_path.as_ref())?;
+
+        let loop_resins = plastic_root
+            .material_sources
+            .into_iter()
+            .map(|m| {
+                let plastic_type = PlasticType::from_resin_code(&m.plastic_type)
+                    .ok_or_else(|| IntakeConfigError::UnknownResin(m.plastic_type.clone()))?;
+                Ok(ResinAcceptance {
+                    plastic_type,
+                    accepted_from: m.accepted_from,
+                    preferred_streams: m.preferred_streams,
+                })
+            })
+            .collect::<Result<Vec<_>, IntakeConfigError>>()?;
+
+        let node_accepted = node_root
+            .accepted_materials
+            .plastics
+            .iter()
+            .map(|entry| {
+                let (code, category) = entry.split_once('_').unwrap_or((entry.as_str(), ""));
+                let plastic_type = PlasticType::from_resin_code(code)
+                    .ok_or_else(|| IntakeConfigError::UnknownResin(entry.clone()))?;
+                Ok((plastic_type, category.to_string()))
+            })
+            .collect::<Result<Vec<_>, IntakeConfigError>>()?;
+
+        Ok(PlasticIntakePolicy {
+            c_node_id: node_root.c_node_id,
+            node_accepted,
+            loop_resins,
+        })
+    }
+
+    pub fn evaluate(&self, stream: &PlasticStream) -> IntakeDecision {
+        let resin = stream.plastic_type;
+        let node_categories: Vec<&str> = self
+            .node_accepted
+            .iter()
+            .filter(|(p, _)| *p == resin)
+            .map(|(_, c)| c.as_str())
+            .collect();
+        if node_categories.is_empty() {
+            return IntakeDecision::Rejected {
+                reasons: vec![format!("{:?} is not accepted at {}", resin, self.c_node_id)],
+            };
+        }
+        // An entry without a category (e.g. plain "PET") takes any category.
+        if let Some(c) = &stream.source_category {
+            if !node_categories.iter().any(|n| n.is_empty() || n == c) {
+                return IntakeDecision::Rejected {
+                    reasons: vec![format!(
+                        "{:?} {} is not accepted at {}",
+                        resin, c, self.c_node_id
+                    )],
+                };
+            }
+        }
+
+        let rules = match self.loop_resins.iter().find(|r| r.plastic_type == resin) {
+            Some(r) => r,
+            None => {
+                return IntakeDecision::Quarantined {
+                    reasons: vec![format!("{:?} is not a device-loop resin", resin)],
+                }
+            }
+        };
+
+        let mut reasons = Vec::new();
+        match &stream.source_category {
+            Some(c) if rules.accepted_from.contains(c) => {}
+            Some(c) => reasons.push(format!(
+                "source category {} not accepted for {:?}",
+                c, resin
+            )),
+            None => reasons.push("source category not reported".to_string()),
+        }
+        match &stream.stream_type {
+            Some(t) if rules.preferred_streams.contains(t) => {}
+            Some(t) => reasons.push(format!("stream type {} not accepted for {:?}", t, resin)),
+            None => reasons.push("stream type not reported".to_string()),
+        }
+
+        if reasons.is_empty() {
+            IntakeDecision::Accepted
+        } else {
+            IntakeDecision::Quarantined { reasons }
+        }
+    }
+
+    /// Split streams into accepted ones and those held back with a decision.
+    pub fn screen<'a>(
+        &self,
+        streams: &'a [PlasticStream],
+    ) -> (
+        Vec<&'a PlasticStream>,
+        Vec<(&'a PlasticStream, IntakeDecision)>,
+    ) {
+        let mut accepted = Vec::new();
+        let mut held = Vec::new();
+        for stream in streams {
+            match self.evaluate(stream) {
+                IntakeDecision::Accepted => accepted.push(stream),
+                decision => held.push((stream, decision)),
+            }
+        }
+        (accepted, held)
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, IntakeConfigError> {
+    let mut buf = String::new();
+    File::open(path)?.read_to_string(&mut buf)?;
+    Ok(serde_json::from_str(&buf)?)
+}
@@ -0,0 +1,470 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::domain::material_flow::MaterialFlowModel;
+use crate::domain::plastic_recycling::{DevicePlasticBatch, PlasticStream, PlasticType};
+
+/// Hash used as `prev_hash` for the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Lot-level events linking streams, batches and device serials.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TraceEvent {
+    /// Mass from a source stream was allocated to a batch.
+    StreamAllocated {
+        source_id: String,
+        plastic_type: PlasticType,
+        mass_kg: f32,
+        batch_id: String,
+    },
+    /// A device shell was produced from a batch.
+    DeviceProduced {
+        batch_id: String,
+        device_serial: String,
+    },
+}
+
+/// One hash-chained ledger entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub sequence: u64,
+    pub recorded_at: DateTime<Utc>,
+    pub event: TraceEvent,
+    /// Hex SHA-256 of the previous entry, or zeros for the first entry.
+    pub prev_hash: String,
+    /// Hex SHA-256 over `prev_hash`, `sequence`, `recorded_at` and `event`.
+    pub hash: String,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LedgerError {
+    #[error("Entry {sequence} could not be serialized for hashing: {message}")]
+    Serialization { sequence: u64, message: String },
+    #[error("Entry {sequence} is out of sequence")]
+    SequenceGap { sequence: u64 },
+    #[error("Entry {sequence} does not link to the previous entry")]
+    BrokenChain { sequence: u64 },
+    #[error("Entry {sequence} content does not match its hash")]
+    HashMismatch { sequence: u64 },
+}
+
+/// A stream's contribution to a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamContribution {
+    pub source_id: String,
+    pub plastic_type: PlasticType,
+    pub mass_kg: f32,
+}
+
+/// Backward trace for a single device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceProvenance {
+    pub device_serial: String,
+    pub batch_id: String,
+    pub streams: Vec<StreamContribution>,
+}
+
+/// Append-only traceability ledger for recycled plastic lots. Entries can
+/// only be appended; `verify` detects any later edit, reordering or removal
+/// of entries other than truncation of the tail.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraceabilityLedger {
+    entries: Vec<TraceEntry>,
+}
+
+impl TraceabilityLedger {
+    pub fn new() -> Self {
+        TraceabilityLedger::default()
+    }
+
+    /// Rebuild a ledger from stored entries, rejecting tampered chains.
+    pub fn from_entries(entries: Vec<TraceEntry>) -> Result<Self, LedgerError> {
+        let ledger = TraceabilityLedger { entries };
+        ledger.verify()?;
+        Ok(ledger)
+    }
+
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// Hash of the most recent entry, for anchoring externally.
+    pub fn head_hash(&self) -> &str {
+        self.entries
+            .last()
+            .map(|e| e.hash.as_str())
+            .unwrap_or(GENESIS_HASH)
+    }
+
+    pub fn append(
+        &mut self,
+        event: TraceEvent,
+        recorded_at: DateTime<Utc>,
+    ) -> Result<&TraceEntry, LedgerError> {
+        let sequence = self.entries.len() as u64;
+        let prev_hash = self.head_hash().to_string();
+        let hash = entry_hash(&prev_hash, sequence, &recorded_at, &event)?;
+        self.entries.push(TraceEntry {
+            sequence,
+            recorded_at,
+            event,
+            prev_hash,
+            hash,
+        });
+        Ok(&self.entries[self.entries.len() - 1])
+    }
+
+    /// Record the streams `model` accepted into `batch`. Streams that
+    /// `build_batch` rejected for quality or resin suitability are left out,
+    /// so the ledger only carries mass that entered processing.
+    pub fn record_batch(
+        &mut self,
+        model: &MaterialFlowModel,
+        batch: &DevicePlasticBatch,
+        streams: &[PlasticStream],
+        recorded_at: DateTime<Utc>,
+    ) -> Result<(), LedgerError> {
+        for stream in streams.iter().filter(|s| model.accepts_for_shells(s)) {
+            self.append(
+                TraceEvent::StreamAllocated {
+                    source_id: stream.source_id.clone(),
+                    plastic_type: stream.plastic_type,
+                    mass_kg: stream.mass_non_negative(),
+                    batch_id: batch.batch_id.clone(),
+                },
+                recorded_at,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Record the device serials produced from a batch.
+    pub fn record_devices(
+        &mut self,
+        batch_id: &str,
+        device_serials: &[String],
+        recorded_at: DateTime<Utc>,
+    ) -> Result<(), LedgerError> {
+        for serial in device_serials {
+            self.append(
+                TraceEvent::DeviceProduced {
+                    batch_id: batch_id.to_string(),
+                    device_serial: serial.clone(),
+                },
+                recorded_at,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Recompute the chain and report the first inconsistent entry.
+    pub fn verify(&self) -> Result<(), LedgerError> {
+        let mut prev_hash = GENESIS_HASH;
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if entry.sequence != idx as u64 {
+                return Err(LedgerError::SequenceGap {
+                    sequence: entry.sequence,
+                });
+            }
+            if entry.prev_hash != prev_hash {
+                return Err(LedgerError::BrokenChain {
+                    sequence: entry.sequence,
+                });
+            }
+            let expected = entry_hash(
+                &entry.prev_hash,
+                entry.sequence,
+                &entry.recorded_at,
+                &entry.event,
+            )?;
+            if entry.hash != expected {
+                return Err(LedgerError::HashMismatch {
+                    sequence: entry.sequence,
+                });
+            }
+            prev_hash = &entry.hash;
+        }
+        Ok(())
+    }
+
+    /// Forward query: serials of devices made from batches that received
+    /// material from `source_id`, optionally restricted to one resin.
+    pub fn devices_containing_source(
+        &self,
+        source_id: &str,
+        plastic_type: Option<PlasticType>,
+    ) -> Vec<String> {
+        let batches: Vec<&str> = self
+            .entries
+            .iter()
+            .filter_map(|e| match &e.event {
+                TraceEvent::StreamAllocated {
+                    source_id: s,
+                    plastic_type: p,
+                    batch_id,
+                    ..
+                } if s == source_id && plastic_type.is_none_or(|want| want == *p) => {
+                    Some(batch_id.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut serials: Vec<String> = self
+            .entries
+            .iter()
+            .filter_map(|e| match &e.event {
+                TraceEvent::DeviceProduced {
+                    batch_id,
+                    device_serial,
+                } if batches.contains(&batch_id.as_str()) => Some(device_serial.clone()),
+                _ => None,
+            })
+            .collect();
+        serials.sort();
+        serials.dedup();
+        serials
+    }
+
+    /// Backward query: the batch and source streams behind a device serial.
+    pub fn device_origin(&self, device_serial: &str) -> Option<DeviceProvenance> {
+        let batch_id = self.entries.iter().find_map(|e| match &e.event {
+            TraceEvent::DeviceProduced {
+                batch_id,
+                device_serial: s,
+            } if s == device_serial => Some(batch_id.clone()),
+            _ => None,
+        })?;
+
+        let streams = self
+            .entries
+            .iter()
+            .filter_map(|e| match &e.event {
+                TraceEvent::StreamAllocated {
+                    source_id,
+                    plastic_type,
+                    mass_kg,
+                    batch_id: b,
+                } if *b == batch_id => Some(StreamContribution {
+                    source_id: source_id.clone(),
+                    plastic_type: *plastic_type,
+                    mass_kg: *mass_kg,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Some(DeviceProvenance {
+            device_serial: device_serial.to_string(),
+            batch_id,
+            streams,
+        })
+    }
+}
+
+fn entry_hash(
+    prev_hash: &str,
+    sequence: u64,
+    recorded_at: &DateTime<Utc>,
+    event: &TraceEvent,
+) -> Result<String, LedgerError> {
+    let event_bytes = serde_json::to_vec(event).map_err(|e| LedgerError::Serialization {
+        sequence,
+        message: e.to_string(),
+    })?;
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(sequence.to_be_bytes());
+    hasher.update(recorded_at.to_rfc3339().as_bytes());
+    hasher.update(event_bytes);
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 3, 1, 9, minute, 0).unwrap()
+    }
+
+    fn allocated(source_id: &str, plastic_type: PlasticType, batch_id: &str) -> TraceEvent {
+        TraceEvent::StreamAllocated {
+            source_id: source_id.to_string(),
+            plastic_type,
+            mass_kg: 10.0,
+            batch_id: batch_id.to_string(),
+        }
+    }
+
+    fn produced(batch_id: &str, device_serial: &str) -> TraceEvent {
+        TraceEvent::DeviceProduced {
+            batch_id: batch_id.to_string(),
+            device_serial: device_serial.to_string(),
+        }
+    }
+
+    /// Two batches: B1 from C1 (PET) and C2 (HDPE), B2 from C2 (PET).
+    fn sample_ledger() -> TraceabilityLedger {
+        let mut ledger = TraceabilityLedger::new();
+        ledger
+            .append(allocated("C1", PlasticType::PET, "B1"), at(0))
+            .unwrap();
+        ledger
+            .append(allocated("C2", PlasticType::HDPE, "B1"), at(1))
+            .unwrap();
+        ledger
+            .append(allocated("C2", PlasticType::PET, "B2"), at(2))
+            .unwrap();
+        ledger.append(produced("B1", "DEV-001"), at(3)).unwrap();
+        ledger.append(produced("B1", "DEV-002"), at(4)).unwrap();
+        ledger.append(produced("B2", "DEV-003"), at(5)).unwrap();
+        ledger
+    }
+
+    #[test]
+    fn test_untampered_ledger_verifies() {
+        let ledger = sample_ledger();
+        assert_eq!(ledger.verify(), Ok(()));
+        assert!(TraceabilityLedger::from_entries(ledger.entries().to_vec()).is_ok());
+    }
+
+    #[test]
+    fn test_edited_entry_is_hash_mismatch() {
+        let mut entries = sample_ledger().entries().to_vec();
+        entries[1].event = allocated("C9", PlasticType::HDPE, "B1");
+        assert_eq!(
+            TraceabilityLedger::from_entries(entries).unwrap_err(),
+            LedgerError::HashMismatch { sequence: 1 }
+        );
+    }
+
+    #[test]
+    fn test_rehashed_edit_breaks_chain() {
+        let mut entries = sample_ledger().entries().to_vec();
+        let e = &mut entries[1];
+        e.event = allocated("C9", PlasticType::HDPE, "B1");
+        e.hash = entry_hash(&e.prev_hash, e.sequence, &e.recorded_at, &e.event).unwrap();
+        assert_eq!(
+            TraceabilityLedger::from_entries(entries).unwrap_err(),
+            LedgerError::BrokenChain { sequence: 2 }
+        );
+    }
+
+    #[test]
+    fn test_reordered_entries_are_sequence_gap() {
+        let mut entries = sample_ledger().entries().to_vec();
+        entries.swap(3, 4);
+        assert_eq!(
+            TraceabilityLedger::from_entries(entries).unwrap_err(),
+            LedgerError::SequenceGap { sequence: 4 }
+        );
+    }
+
+    #[test]
+    fn test_reordered_and_renumbered_entries_break_chain() {
+        let mut entries = sample_ledger().entries().to_vec();
+        entries.swap(3, 4);
+        entries[3].sequence = 3;
+        entries[4].sequence = 4;
+        assert_eq!(
+            TraceabilityLedger::from_entries(entries).unwrap_err(),
+            LedgerError::BrokenChain { sequence: 3 }
+        );
+    }
+
+    #[test]
+    fn test_removed_entry_is_sequence_gap() {
+        let mut entries = sample_ledger().entries().to_vec();
+        entries.remove(2);
+        assert_eq!(
+            TraceabilityLedger::from_entries(entries).unwrap_err(),
+            LedgerError::SequenceGap { sequence: 3 }
+        );
+    }
+
+    #[test]
+    fn test_devices_containing_source() {
+        let ledger = sample_ledger();
+        assert_eq!(
+            ledger.devices_containing_source("C2", None),
+            vec!["DEV-001", "DEV-002", "DEV-003"]
+        );
+        assert_eq!(
+            ledger.devices_containing_source("C2", Some(PlasticType::PET)),
+            vec!["DEV-003"]
+        );
+        assert!(ledger.devices_containing_source("C7", None).is_empty());
+    }
+
+    #[test]
+    fn test_device_origin() {
+        let ledger = sample_ledger();
+        let origin = ledger.device_origin("DEV-002").unwrap();
+        assert_eq!(origin.batch_id, "B1");
+        let sources: Vec<(&str, PlasticType)> = origin
+            .streams
+            .iter()
+            .map(|s| (s.source_id.as_str(), s.plastic_type))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![("C1", PlasticType::PET), ("C2", PlasticType::HDPE)]
+        );
+        assert!(ledger.device_origin("DEV-404").is_none());
+    }
+
+    #[test]
+    fn test_record_batch_skips_rejected_streams() {
+        use crate::domain::material_flow::{QualityRequirements, ResinProcessProfile};
+
+        let model = MaterialFlowModel {
+            resins: vec![ResinProcessProfile {
+                plastic_type: PlasticType::PET,
+                sorting_yield: 0.9,
+                washing_yield: 0.9,
+                pelletizing_yield: 0.9,
+                suitable_for_device_shells: true,
+            }],
+            quality: QualityRequirements {
+                max_contaminant_percent: 5.0,
+                moisture_threshold_percent: 2.0,
+            },
+        };
+        let stream = |source_id: &str, plastic_type, contaminant_percent| PlasticStream {
+            source_id: source_id.to_string(),
+            plastic_type,
+            mass_kg: 10.0,
+            source_category: None,
+            stream_type: None,
+            contaminant_percent,
+            moisture_percent: None,
+        };
+        let streams = vec![
+            stream("CLEAN", PlasticType::PET, Some(1.0)),
+            stream("DIRTY", PlasticType::PET, Some(20.0)),
+            stream("HDPE", PlasticType::HDPE, None),
+        ];
+        let batch = model.build_batch("B1".to_string(), &streams, 0.0, 0.05);
+
+        let mut ledger = TraceabilityLedger::new();
+        ledger
+            .record_batch(&model, &batch, &streams, at(0))
+            .unwrap();
+        let recorded: Vec<&str> = ledger
+            .entries()
+            .iter()
+            .filter_map(|e| match &e.event {
+                TraceEvent::StreamAllocated { source_id, .. } => Some(source_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(recorded, vec!["CLEAN"]);
+    }
+}
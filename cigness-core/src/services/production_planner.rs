@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::plastic_recycling::PlasticType;
+
+/// Products an M-node can make, matching `outputs.daily_capacity` keys in
+/// m_node_profile.cigness.json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MNodeProduct {
+    DeviceShell,
+    ReusableCrate,
+    WaterContainer,
+}
+
+impl MNodeProduct {
+    pub fn capacity_key(&self) -> &'static str {
+        match self {
+            MNodeProduct::DeviceShell => "device_shells",
+            MNodeProduct::ReusableCrate => "reusable_crates",
+            MNodeProduct::WaterContainer => "water_containers",
+        }
+    }
+}
+
+/// Pellet mass of one resin needed per unit of product.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResinRequirement {
+    pub plastic_type: PlasticType,
+    pub kg_per_unit: f32,
+}
+
+/// Bill of materials and planning weight for one product.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductSpec {
+    pub product: MNodeProduct,
+    pub bill_of_materials: Vec<ResinRequirement>,
+    /// Objective weight per unit produced (e.g., program priority score).
+    pub value_per_unit: f32,
+    pub daily_capacity_units: u32,
+}
+
+impl ProductSpec {
+    fn kg_per_unit_total(&self) -> f32 {
+        self.bill_of_materials
+            .iter()
+            .map(|r| r.kg_per_unit.max(0.0))
+            .sum()
+    }
+
+    /// Pellet mass per unit for each resin, summing repeated BOM lines.
+    fn kg_per_unit_by_resin(&self) -> HashMap<PlasticType, f32> {
+        let mut per_resin = HashMap::new();
+        for r in self
+            .bill_of_materials
+            .iter()
+            .filter(|r| r.kg_per_unit > 0.0)
+        {
+            *per_resin.entry(r.plastic_type).or_insert(0.0) += r.kg_per_unit;
+        }
+        per_resin
+    }
+
+    /// Units producible from `inventory`, limited by daily capacity.
+    fn max_units(&self, inventory: &HashMap<PlasticType, f32>) -> u32 {
+        self.kg_per_unit_by_resin()
+            .into_iter()
+            .map(|(plastic_type, kg_per_unit)| {
+                let available = inventory.get(&plastic_type).copied().unwrap_or(0.0);
+                let mut units = (available.max(0.0) / kg_per_unit).floor() as u32;
+                // Float rounding can put the floor one unit over stock.
+                while units > 0 && units as f32 * kg_per_unit > available {
+                    units -= 1;
+                }
+                units
+            })
+            .min()
+            .unwrap_or(0)
+            .min(self.daily_capacity_units)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledProduct {
+    pub product: MNodeProduct,
+    pub units: u32,
+    pub material_used_kg: HashMap<PlasticType, f32>,
+    /// Scheduled units over daily capacity (0.0–1.0).
+    pub capacity_utilization: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductionSchedule {
+    pub lines: Vec<ScheduledProduct>,
+    pub total_value: f32,
+    pub leftover_inventory_kg: HashMap<PlasticType, f32>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PlanningError {
+    #[error("{product:?} needs {required_kg} kg of {plastic_type:?} but only {available_kg} kg is in stock")]
+    InsufficientResin {
+        product: MNodeProduct,
+        plastic_type: PlasticType,
+        required_kg: f32,
+        available_kg: f32,
+    },
+}
+
+/// Wire format of `outputs.daily_capacity` in m_node_profile.cigness.json.
+#[derive(Debug, Deserialize)]
+struct MNodeProfileRoot {
+    outputs: MNodeOutputs,
+}
+
+#[derive(Debug, Deserialize)]
+struct MNodeOutputs {
+    daily_capacity: HashMap<String, u32>,
+}
+
+pub struct ProductionPlanner;
+
+impl ProductionPlanner {
+    /// Read daily capacities keyed by product from an M-node profile.
+    pub fn load_daily_capacity<P: AsRef<Path>>(
+        m_node_profile_path: P,
+    ) -> Result<HashMap<MNodeProduct, u32>, String> {
+        let mut buf = String::new();
+        File::open(m_node_profile_path.as_ref())
+            .and_then(|mut f| f.read_to_string(&mut buf))
+            .map_err(|e| e.to_string())?;
+        let root: MNodeProfileRoot = serde_json::from_str(&buf).map_err(|e| e.to_string())?;
+
+        let products = [
+            MNodeProduct::DeviceShell,
+            MNodeProduct::ReusableCrate,
+            MNodeProduct::WaterContainer,
+        ];
+        Ok(products
+            .iter()
+            .filter_map(|p| {
+                root.outputs
+                    .daily_capacity
+                    .get(p.capacity_key())
+                    .map(|c| (*p, *c))
+            })
+            .collect())
+    }
+
+    /// Allocate pellet inventory across products for one day.
+    ///
+    /// Greedy heuristic: products are scheduled in descending order of value
+    /// per kg of pellets, each up to its capacity or until a resin in its
+    /// bill of materials runs out. Products with an empty bill of materials
+    /// or zero value are skipped. Fails if a scheduled line would draw more
+    /// of a resin than is left in stock.
+    pub fn plan_day(
+        inventory_kg: &HashMap<PlasticType, f32>,
+        products: &[ProductSpec],
+    ) -> Result<ProductionSchedule, PlanningError> {
+        let mut inventory: HashMap<PlasticType, f32> =
+            inventory_kg.iter().map(|(k, v)| (*k, v.max(0.0))).collect();
+
+        let mut ranked: Vec<&ProductSpec> = products
+            .iter()
+            .filter(|p| p.kg_per_unit_total() > 0.0 && p.value_per_unit > 0.0)
+            .collect();
+        ranked.sort_by(|a, b| {
+            let da = a.value_per_unit / a.kg_per_unit_total();
+            let db = b.value_per_unit / b.kg_per_unit_total();
+            db.total_cmp(&da)
+        });
+
+        let mut lines = Vec::new();
+        let mut total_value = 0.0_f32;
+        for spec in ranked {
+            let units = spec.max_units(&inventory);
+            let mut material_used_kg = HashMap::new();
+            for (plastic_type, kg_per_unit) in spec.kg_per_unit_by_resin() {
+                let used = units as f32 * kg_per_unit;
+                if used == 0.0 {
+                    continue;
+                }
+                let stock = inventory.entry(plastic_type).or_insert(0.0);
+                if used > *stock {
+                    return Err(PlanningError::InsufficientResin {
+                        product: spec.product,
+                        plastic_type,
+                        required_kg: used,
+                        available_kg: *stock,
+                    });
+                }
+                *stock -= used;
+                material_used_kg.insert(plastic_type, used);
+            }
+            total_value += spec.value_per_unit * units as f32;
+            lines.push(ScheduledProduct {
+                product: spec.product,
+                units,
+                material_used_kg,
+                capacity_utilization: if spec.daily_capacity_units > 0 {
+                    units as f32 / spec.daily_capacity_units as f32
+                } else {
+                    0.0
+                },
+            });
+        }
+
+        Ok(ProductionSchedule {
+            lines,
+            total_value,
+            leftover_inventory_kg: inventory,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirement(plastic_type: PlasticType, kg_per_unit: f32) -> ResinRequirement {
+        ResinRequirement {
+            plastic_type,
+            kg_per_unit,
+        }
+    }
+
+    #[test]
+    fn test_repeated_resin_lines_are_summed() {
+        let inventory = [(PlasticType::PET, 10.0)].into_iter().collect();
+        let spec = ProductSpec {
+            product: MNodeProduct::DeviceShell,
+            bill_of_materials: vec![
+                requirement(PlasticType::PET, 0.5),
+                requirement(PlasticType::PET, 0.5),
+            ],
+            value_per_unit: 1.0,
+            daily_capacity_units: 100,
+        };
+        let schedule = ProductionPlanner::plan_day(&inventory, &[spec]).unwrap();
+        assert_eq!(schedule.lines[0].units, 10);
+        assert_eq!(schedule.lines[0].material_used_kg[&PlasticType::PET], 10.0);
+        assert_eq!(schedule.leftover_inventory_kg[&PlasticType::PET], 0.0);
+    }
+
+    #[test]
+    fn test_shared_resin_is_not_overdrawn() {
+        let inventory = [(PlasticType::PET, 30.0), (PlasticType::HDPE, 100.0)]
+            .into_iter()
+            .collect();
+        let specs = vec![
+            ProductSpec {
+                product: MNodeProduct::DeviceShell,
+                bill_of_materials: vec![requirement(PlasticType::PET, 0.05)],
+                value_per_unit: 1.0,
+                daily_capacity_units: 500,
+            },
+            ProductSpec {
+                product: MNodeProduct::WaterContainer,
+                bill_of_materials: vec![
+                    requirement(PlasticType::HDPE, 0.3),
+                    requirement(PlasticType::PET, 0.01),
+                ],
+                value_per_unit: 2.0,
+                daily_capacity_units: 800,
+            },
+        ];
+        let schedule = ProductionPlanner::plan_day(&inventory, &specs).unwrap();
+        for (plastic_type, start) in [(PlasticType::PET, 30.0), (PlasticType::HDPE, 100.0)] {
+            let used: f32 = schedule
+                .lines
+                .iter()
+                .filter_map(|l| l.material_used_kg.get(&plastic_type))
+                .sum();
+            assert!(used <= start + 1e-3);
+            assert!(schedule.leftover_inventory_kg[&plastic_type] >= 0.0);
+        }
+    }
+}
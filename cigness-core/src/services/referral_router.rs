@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::smoker_profile::{AgeMode, SmokerProfile};
+use crate::services::cessation_plan::CessationPlan;
+
+/// Program a partner must offer to receive cessation referrals.
+pub const CESSATION_PROGRAM: &str = "cessation_support";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollaborationStatus {
+    Active,
+    Invited,
+    Pending,
+    #[serde(other)]
+    Other,
+}
+
+/// Partner entry from dashboard/partner_registry.cigness.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Partner {
+    pub partner_id: String,
+    pub name: String,
+    pub organization_type: String,
+    pub city: String,
+    pub county: String,
+    pub programs_offered: Vec<String>,
+    pub youth_prevention_focus: bool,
+    pub eco_alignment_score: f32,
+    pub collaboration_status: CollaborationStatus,
+    pub primary_contact_channel: String,
+}
+
+impl Partner {
+    pub fn offers(&self, program: &str) -> bool {
+        self.programs_offered.iter().any(|p| p == program)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PartnerRegistryRoot {
+    version: String,
+    partners: Vec<Partner>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReferralError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Plan for {plan} cannot route a referral for profile {profile}")]
+    ProfileMismatch { plan: String, profile: String },
+    #[error("Plan for {0} does not recommend a referral")]
+    NotRecommended(String),
+    #[error("No county known for region {0}")]
+    UnknownCounty(String),
+    #[error("No active cessation partner in county {0}")]
+    NoActivePartner(String),
+}
+
+/// Outcome of routing one plan to a partner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralRecord {
+    pub profile_id: String,
+    pub partner_id: String,
+    pub partner_name: String,
+    pub county: String,
+    pub created_at: DateTime<Utc>,
+    /// Other eligible partners, best first, in case the first declines.
+    pub alternates: Vec<String>,
+    /// Registry version the decision was made against.
+    pub registry_version: String,
+}
+
+/// Routes cessation referrals to active partners in the person's county.
+#[derive(Debug, Clone)]
+pub struct ReferralRouter {
+    pub registry_version: String,
+    pub partners: Vec<Partner>,
+    /// Region code (e.g. "US-AZ-MARICOPA") to county name.
+    pub region_counties: HashMap<String, String>,
+}
+
+impl ReferralRouter {
+    pub fn load_from_file<P: AsRef<Path>>(
+        partner_registry_path: P,
+        region_counties: HashMap<String, String>,
+    ) -> Result<Self, ReferralError> {
+        let mut buf = String::new();
+        File::open(partner_registry_path.as_ref())?.read_to_string(&mut buf)?;
+        let root: PartnerRegistryRoot = serde_json::from_str(&buf)?;
+        Ok(ReferralRouter {
+            registry_version: root.version,
+            partners: root.partners,
+            region_counties,
+        })
+    }
+
+    /// County for a region code, trying the full code and then each shorter
+    /// prefix (e.g. "US-AZ-MARICOPA" → "US-AZ").
+    pub fn county_for_region(&self, region_code: &str) -> Option<&str> {
+        let mut code = region_code.trim();
+        while !code.is_empty() {
+            if let Some(county) = self.region_counties.get(code) {
+                return Some(county.as_str());
+            }
+            code = match code.rfind('-') {
+                Some(idx) => &code[..idx],
+                None => "",
+            };
+        }
+        None
+    }
+
+    /// Active cessation partners in `county`, best first. Youth profiles
+    /// prefer partners with a youth-prevention focus; ties are broken by
+    /// eco alignment score and then partner id.
+    pub fn rank_partners(&self, county: &str, age_mode: Option<AgeMode>) -> Vec<&Partner> {
+        let prefer_youth = age_mode == Some(AgeMode::Youth);
+        let mut eligible: Vec<&Partner> = self
+            .partners
+            .iter()
+            .filter(|p| p.collaboration_status == CollaborationStatus::Active)
+            .filter(|p| p.offers(CESSATION_PROGRAM))
+            .filter(|p| p.county.eq_ignore_ascii_case(county))
+            .collect();
+        eligible.sort_by(|a, b| {
+            let youth_a = prefer_youth && a.youth_prevention_focus;
+            let youth_b = prefer_youth && b.youth_prevention_focus;
+            youth_b
+                .cmp(&youth_a)
+                .then(b.eco_alignment_score.total_cmp(&a.eco_alignment_score))
+                .then(a.partner_id.cmp(&b.partner_id))
+        });
+        eligible
+    }
+
+    pub fn route(
+        &self,
+        plan: &CessationPlan,
+        profile: &SmokerProfile,
+        created_at: DateTime<Utc>,
+    ) -> Result<ReferralRecord, ReferralError> {
+        if plan.profile_id != profile.profile_id {
+            return Err(ReferralError::ProfileMismatch {
+                plan: plan.profile_id.clone(),
+                profile: profile.profile_id.clone(),
+            });
+        }
+        if !plan.referral_recommended {
+            return Err(ReferralError::NotRecommended(plan.profile_id.clone()));
+        }
+        let county = self
+            .county_for_region(&profile.region_code)
+            .ok_or_else(|| ReferralError::UnknownCounty(profile.region_code.clone()))?;
+
+        let ranked = self.rank_partners(county, profile.age_mode());
+        let (first, rest) = ranked
+            .split_first()
+            .ok_or_else(|| ReferralError::NoActivePartner(county.to_string()))?;
+
+        Ok(ReferralRecord {
+            profile_id: plan.profile_id.clone(),
+            partner_id: first.partner_id.clone(),
+            partner_name: first.name.clone(),
+            county: county.to_string(),
+            created_at,
+            alternates: rest.iter().map(|p| p.partner_id.clone()).collect(),
+            registry_version: self.registry_version.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router() -> ReferralRouter {
+        ReferralRouter {
+            registry_version: "1.0.0".to_string(),
+            partners: vec![Partner {
+                partner_id: "P1".to_string(),
+                name: "Maricopa Quitline".to_string(),
+                organization_type: "public_health".to_string(),
+                city: "Phoenix".to_string(),
+                county: "Maricopa".to_string(),
+                programs_offered: vec![CESSATION_PROGRAM.to_string()],
+                youth_prevention_focus: false,
+                eco_alignment_score: 0.5,
+                collaboration_status: CollaborationStatus::Active,
+                primary_contact_channel: "phone".to_string(),
+            }],
+            region_counties: HashMap::from([("US-AZ".to_string(), "Maricopa".to_string())]),
+        }
+    }
+
+    fn profile(profile_id: &str) -> SmokerProfile {
+        SmokerProfile {
+            profile_id: profile_id.to_string(),
+            age_years: 40,
+            packs_per_day: 1.0,
+            years_smoked: 20.0,
+            has_diagnosed_condition: false,
+            reports_severe_anxiety: false,
+            clinician_recommended_cessation: true,
+            region_code: "US-AZ".to_string(),
+            other_products: Vec::new(),
+        }
+    }
+
+    fn plan(profile_id: &str) -> CessationPlan {
+        CessationPlan {
+            profile_id: profile_id.to_string(),
+            baseline_packs_per_day: 1.0,
+            target_packs_per_day_90d: 0.25,
+            referral_recommended: true,
+            monitoring_interval_days: 14,
+        }
+    }
+
+    #[test]
+    fn test_route_matching_profile() {
+        let record = router()
+            .route(&plan("user-1"), &profile("user-1"), Utc::now())
+            .unwrap();
+        assert_eq!(record.partner_id, "P1");
+        assert_eq!(record.county, "Maricopa");
+    }
+
+    #[test]
+    fn test_route_rejects_plan_for_other_profile() {
+        let err = router()
+            .route(&plan("user-1"), &profile("user-2"), Utc::now())
+            .unwrap_err();
+        assert!(matches!(err, ReferralError::ProfileMismatch { .. }));
+    }
+}
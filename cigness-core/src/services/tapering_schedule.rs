@@ -0,0 +1,164 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::health_risk::HealthRiskReport;
+use crate::domain::smoker_profile::HealthRiskBand;
+use crate::services::cessation_plan::CessationPlan;
+
+/// Length of a cessation plan, matching `target_packs_per_day_90d`.
+pub const PLAN_HORIZON_DAYS: i64 = 90;
+/// Default lead time to a quit date for Critical band plans.
+const DEFAULT_QUIT_LEAD_DAYS: i64 = 14;
+/// Number of equal steps in a step-down taper.
+const STEP_DOWN_STEPS: f32 = 4.0;
+/// Curvature of the exponential taper; higher front-loads more reduction.
+const EXPONENTIAL_RATE: f32 = 3.0;
+
+/// Shape of the path from baseline to target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaperShape {
+    /// Equal weekly reductions.
+    Linear,
+    /// Larger reductions early, smaller later.
+    Exponential,
+    /// A few plateaus with a reduction between each.
+    StepDown,
+    /// Linear reduction to zero by a quit date, then zero.
+    QuitDate,
+}
+
+impl TaperShape {
+    /// Default shape for a risk band: the higher the risk, the faster the
+    /// early reduction.
+    pub fn for_risk_band(band: &HealthRiskBand) -> TaperShape {
+        match band {
+            HealthRiskBand::Critical => TaperShape::QuitDate,
+            HealthRiskBand::High => TaperShape::Exponential,
+            HealthRiskBand::Moderate => TaperShape::StepDown,
+            HealthRiskBand::Low => TaperShape::Linear,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyTarget {
+    /// 1-based week number within the plan.
+    pub week: u32,
+    pub week_start: NaiveDate,
+    /// Target packs/day to reach by the end of this week.
+    pub target_packs_per_day: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaperingSchedule {
+    pub profile_id: String,
+    pub shape: TaperShape,
+    pub start_date: NaiveDate,
+    /// Set only for `TaperShape::QuitDate`.
+    pub quit_date: Option<NaiveDate>,
+    pub weekly_targets: Vec<WeeklyTarget>,
+    /// Planned check-ins every `monitoring_interval_days` within the plan.
+    pub check_in_dates: Vec<NaiveDate>,
+}
+
+impl TaperingSchedule {
+    /// Target in effect on `date`, or None before the plan starts.
+    pub fn target_on(&self, date: NaiveDate) -> Option<f32> {
+        if date < self.start_date {
+            return None;
+        }
+        self.weekly_targets
+            .iter()
+            .find(|w| date < w.week_start + Duration::days(7))
+            .or(self.weekly_targets.last())
+            .map(|w| w.target_packs_per_day)
+    }
+}
+
+pub struct TaperingScheduler;
+
+impl TaperingScheduler {
+    /// Build a weekly schedule for `plan`. The shape follows the risk band;
+    /// Critical plans use `quit_date`, defaulting to two weeks after start.
+    pub fn build_schedule(
+        plan: &CessationPlan,
+        risk: &HealthRiskReport,
+        start_date: NaiveDate,
+        quit_date: Option<NaiveDate>,
+    ) -> TaperingSchedule {
+        let shape = TaperShape::for_risk_band(&risk.risk_band);
+        Self::build_schedule_with_shape(plan, shape, start_date, quit_date)
+    }
+
+    /// Build a schedule with an explicit shape, e.g. when a counselor
+    /// overrides the default for the risk band.
+    pub fn build_schedule_with_shape(
+        plan: &CessationPlan,
+        shape: TaperShape,
+        start_date: NaiveDate,
+        quit_date: Option<NaiveDate>,
+    ) -> TaperingSchedule {
+        let end_date = start_date + Duration::days(PLAN_HORIZON_DAYS);
+        let baseline = plan.baseline_packs_per_day.max(0.0);
+        let target = plan.target_packs_per_day_90d.clamp(0.0, baseline);
+
+        let quit_date = match shape {
+            TaperShape::QuitDate => Some(
+                quit_date
+                    .unwrap_or(start_date + Duration::days(DEFAULT_QUIT_LEAD_DAYS))
+                    .clamp(start_date, end_date),
+            ),
+            _ => None,
+        };
+
+        let total_weeks = (PLAN_HORIZON_DAYS + 6) / 7;
+        let weekly_targets = (1..=total_weeks)
+            .map(|week| {
+                let week_start = start_date + Duration::days((week - 1) * 7);
+                let week_end = (week_start + Duration::days(7)).min(end_date);
+                let progress = (week_end - start_date).num_days() as f32 / PLAN_HORIZON_DAYS as f32;
+                let target_packs_per_day = match (shape, quit_date) {
+                    (TaperShape::QuitDate, Some(q)) => {
+                        let lead = (q - start_date).num_days().max(1) as f32;
+                        let quit_progress =
+                            ((week_end - start_date).num_days() as f32 / lead).min(1.0);
+                        baseline * (1.0 - quit_progress)
+                    }
+                    _ => baseline - (baseline - target) * shape_fraction(shape, progress),
+                };
+                WeeklyTarget {
+                    week: week as u32,
+                    week_start,
+                    target_packs_per_day: target_packs_per_day.max(0.0),
+                }
+            })
+            .collect();
+
+        let interval = plan.monitoring_interval_days.max(1) as i64;
+        let check_in_dates = (1..)
+            .map(|n| start_date + Duration::days(n * interval))
+            .take_while(|d| *d <= end_date)
+            .collect();
+
+        TaperingSchedule {
+            profile_id: plan.profile_id.clone(),
+            shape,
+            start_date,
+            quit_date,
+            weekly_targets,
+            check_in_dates,
+        }
+    }
+}
+
+/// Fraction (0.0–1.0) of the total reduction achieved at `progress`.
+fn shape_fraction(shape: TaperShape, progress: f32) -> f32 {
+    let p = progress.clamp(0.0, 1.0);
+    match shape {
+        TaperShape::Linear | TaperShape::QuitDate => p,
+        TaperShape::Exponential => {
+            (1.0 - (-EXPONENTIAL_RATE * p).exp()) / (1.0 - (-EXPONENTIAL_RATE).exp())
+        }
+        TaperShape::StepDown => (p * STEP_DOWN_STEPS).floor() / STEP_DOWN_STEPS,
+    }
+}
@@ -4,7 +4,7 @@ use std::path::Path;
 
 use serde::Deserialize;
 
-use super::token_rewards::TokenPolicy;
+use super::token_rewards::{RedemptionPathPolicy, RedemptionPathType, TokenPolicy};
 
 /// Wire format aligned with cigness.runtime.policy.json and
 /// cigness.plastic-loop.json (token_reward_model).
@@ -88,12 +88,12 @@ struct PostQuitBonus {
     eco_focus_multiplier: f32,
 }
 
+/// Path types are kept as strings so that a type this build does not know
+/// only matters if the path is actually allowed.
 #[derive(Debug, Deserialize)]
 struct RedemptionPath {
     id: String,
-    #[allow(dead_code)]
     r#type: String,
-    #[allow(dead_code)]
     conversion_rate_cig_impact_to_fiat_equivalent: f32,
 }
 
@@ -110,7 +110,13 @@ impl TokenPolicyLoader {
             Self::read_json(runtime_policy_path.as_ref()).map_err(|e| e.to_string())?;
         let plastic_root: PlasticLoopRoot =
             Self::read_json(plastic_loop_path.as_ref()).map_err(|e| e.to_string())?;
+        Self::merge(&runtime_root, &plastic_root)
+    }
 
+    fn merge(
+        runtime_root: &RuntimePolicyRoot,
+        plastic_root: &PlasticLoopRoot,
+    ) -> Result<TokenPolicy, String> {
         // Enforce same token symbol and non-transferable semantics at this layer.
         if runtime_root.cigness_policies.token_rewards.token_symbol
             != plastic_root.token_reward_model.token_symbol
@@ -136,8 +142,34 @@ impl TokenPolicyLoader {
             max_daily_tokens_per_user: tr.caps.max_daily_tokens_per_user.max(0.0),
             max_annual_tokens_per_user: tr.caps.max_annual_tokens_per_user.max(0.0),
             post_quit_eligible_after_days: pl.post_quit_bonus.eligible_after_days_smoke_free.max(0),
+            redemption_paths: Vec::new(),
         };
 
+        // Only paths named in allow_redemption_to can receive redemptions;
+        // unknown types on other paths are ignored.
+        let allowed = &tr.cross_system_contributions.allow_redemption_to;
+        for id in allowed {
+            let path = pl
+                .redemption_paths
+                .iter()
+                .find(|p| &p.id == id)
+                .ok_or_else(|| format!("Allowed redemption path {} has no conversion rate", id))?;
+            if !path.conversion_rate_cig_impact_to_fiat_equivalent.is_finite()
+                || path.conversion_rate_cig_impact_to_fiat_equivalent < 0.0
+            {
+                return Err(format!("Invalid conversion rate for redemption path {}", id));
+            }
+            let path_type = RedemptionPathType::from_code(&path.r#type).ok_or_else(|| {
+                format!("Redemption path {} has unknown type {}", id, path.r#type)
+            })?;
+            policy.redemption_paths.push(RedemptionPathPolicy {
+                id: path.id.clone(),
+                path_type,
+                conversion_rate_cig_impact_to_fiat_equivalent: path
+                    .conversion_rate_cig_impact_to_fiat_equivalent,
+            });
+        }
+
         // Hard fairness & anti-gambling constraints:
         //  - No multiplier can exceed 3.0
         //  - Daily and annual caps must be finite and strictly bounded
@@ -173,3 +205,50 @@ impl TokenPolicyLoader {
         Ok(parsed)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUNTIME_POLICY: &str = include_str!("../../../configs/cigness.runtime.policy.json");
+    const PLASTIC_LOOP: &str = include_str!("../../../configs/cigness.plastic-loop.json");
+
+    fn plastic_loop_with_extra_path(id: &str, path_type: &str) -> PlasticLoopRoot {
+        let mut value: serde_json::Value = serde_json::from_str(PLASTIC_LOOP).unwrap();
+        value["token_reward_model"]["redemption_paths"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({
+                "id": id,
+                "type": path_type,
+                "conversion_rate_cig_impact_to_fiat_equivalent": 0.01
+            }));
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_unknown_type_on_unlisted_path_is_ignored() {
+        let runtime: RuntimePolicyRoot = serde_json::from_str(RUNTIME_POLICY).unwrap();
+        let plastic = plastic_loop_with_extra_path("CARBON-OFFSETS", "OFFSET");
+        let policy = TokenPolicyLoader::merge(&runtime, &plastic).unwrap();
+        assert_eq!(policy.redemption_paths.len(), 3);
+        assert!(policy.redemption_path("CARBON-OFFSETS").is_none());
+    }
+
+    #[test]
+    fn test_unknown_type_on_allowed_path_names_the_path() {
+        let mut runtime: serde_json::Value = serde_json::from_str(RUNTIME_POLICY).unwrap();
+        runtime["cigness_policies"]["token_rewards"]["cross_system_contributions"]
+            ["allow_redemption_to"]
+            .as_array_mut()
+            .unwrap()
+            .push("CARBON-OFFSETS".into());
+        let runtime: RuntimePolicyRoot = serde_json::from_value(runtime).unwrap();
+        let plastic = plastic_loop_with_extra_path("CARBON-OFFSETS", "OFFSET");
+        let err = TokenPolicyLoader::merge(&runtime, &plastic).unwrap_err();
+        assert_eq!(
+            err,
+            "Redemption path CARBON-OFFSETS has unknown type OFFSET"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::services::token_rewards::{RedemptionPathType, TokenPolicy};
+
+#[derive(Debug, Error)]
+pub enum RedemptionError {
+    #[error("Redemption path {0} is not allowed by policy")]
+    PathNotAllowed(String),
+    #[error("Redemption amount must be positive and finite: {0}")]
+    InvalidAmount(f32),
+    #[error("Insufficient balance for {profile_id}: requested {requested}, available {available}")]
+    InsufficientBalance {
+        profile_id: String,
+        requested: f32,
+        available: f32,
+    },
+}
+
+/// One redemption from a user's balance into a fund. Tokens only ever
+/// leave a user towards a fund; there is no user-to-user transfer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedemptionTransaction {
+    /// Sequence number within the ledger, starting at 1.
+    pub sequence: u64,
+    pub profile_id: String,
+    pub path_id: String,
+    pub path_type: RedemptionPathType,
+    pub token_symbol: String,
+    pub tokens_redeemed: f32,
+    pub conversion_rate: f32,
+    pub fiat_equivalent: f32,
+    pub redeemed_at: DateTime<Utc>,
+}
+
+/// Aggregate of all redemptions into one fund, for reporting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundTotal {
+    pub path_id: String,
+    pub path_type: RedemptionPathType,
+    pub tokens_redeemed: f32,
+    pub fiat_equivalent: f32,
+    pub transaction_count: usize,
+}
+
+/// Append-only record of redemptions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedemptionLedger {
+    transactions: Vec<RedemptionTransaction>,
+}
+
+impl RedemptionLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn transactions(&self) -> &[RedemptionTransaction] {
+        &self.transactions
+    }
+
+    /// Total tokens `profile_id` has redeemed so far.
+    pub fn redeemed_by(&self, profile_id: &str) -> f32 {
+        self.transactions
+            .iter()
+            .filter(|t| t.profile_id == profile_id)
+            .map(|t| t.tokens_redeemed)
+            .sum()
+    }
+
+    /// Redeem `tokens` from `profile_id` to `path_id`. `minted_total` is
+    /// everything minted to the user; earlier redemptions in this ledger
+    /// are deducted from it to find the available balance.
+    pub fn redeem(
+        &mut self,
+        policy: &TokenPolicy,
+        profile_id: &str,
+        minted_total: f32,
+        path_id: &str,
+        tokens: f32,
+        redeemed_at: DateTime<Utc>,
+    ) -> Result<&RedemptionTransaction, RedemptionError> {
+        let path = policy
+            .redemption_path(path_id)
+            .ok_or_else(|| RedemptionError::PathNotAllowed(path_id.to_string()))?;
+        if !tokens.is_finite() || tokens <= 0.0 {
+            return Err(RedemptionError::InvalidAmount(tokens));
+        }
+        let available = (minted_total - self.redeemed_by(profile_id)).max(0.0);
+        if tokens > available {
+            return Err(RedemptionError::InsufficientBalance {
+                profile_id: profile_id.to_string(),
+                requested: tokens,
+                available,
+            });
+        }
+
+        let rate = path.conversion_rate_cig_impact_to_fiat_equivalent;
+        self.transactions.push(RedemptionTransaction {
+            sequence: self.transactions.len() as u64 + 1,
+            profile_id: profile_id.to_string(),
+            path_id: path.id.clone(),
+            path_type: path.path_type,
+            token_symbol: policy.token_symbol.clone(),
+            tokens_redeemed: tokens,
+            conversion_rate: rate,
+            fiat_equivalent: tokens * rate,
+            redeemed_at,
+        });
+        Ok(self.transactions.last().expect("just pushed"))
+    }
+
+    /// Totals per fund, ordered by path id.
+    pub fn fund_totals(&self) -> Vec<FundTotal> {
+        let mut totals: BTreeMap<&str, FundTotal> = BTreeMap::new();
+        for t in &self.transactions {
+            let entry = totals.entry(t.path_id.as_str()).or_insert(FundTotal {
+                path_id: t.path_id.clone(),
+                path_type: t.path_type,
+                tokens_redeemed: 0.0,
+                fiat_equivalent: 0.0,
+                transaction_count: 0,
+            });
+            entry.tokens_redeemed += t.tokens_redeemed;
+            entry.fiat_equivalent += t.fiat_equivalent;
+            entry.transaction_count += 1;
+        }
+        totals.into_values().collect()
+    }
+}
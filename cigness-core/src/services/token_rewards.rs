@@ -16,6 +16,45 @@ pub struct TokenPolicy {
     pub max_daily_tokens_per_user: f32,
     pub max_annual_tokens_per_user: f32,
     pub post_quit_eligible_after_days: i64,
+    /// Redemption paths allowed by the runtime policy, with their rates.
+    #[serde(default)]
+    pub redemption_paths: Vec<RedemptionPathPolicy>,
+}
+
+impl TokenPolicy {
+    pub fn redemption_path(&self, path_id: &str) -> Option<&RedemptionPathPolicy> {
+        self.redemption_paths.iter().find(|p| p.id == path_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedemptionPathType {
+    #[serde(rename = "DONATION")]
+    Donation,
+    #[serde(rename = "INFRA-REINVEST")]
+    InfraReinvest,
+}
+
+impl RedemptionPathType {
+    /// Parse a path type as written in the plastic-loop config
+    /// (e.g. "DONATION", "INFRA-REINVEST").
+    pub fn from_code(code: &str) -> Option<RedemptionPathType> {
+        match code.trim() {
+            "DONATION" => Some(RedemptionPathType::Donation),
+            "INFRA-REINVEST" => Some(RedemptionPathType::InfraReinvest),
+            _ => None,
+        }
+    }
+}
+
+/// A fund tokens can be redeemed to, from the plastic-loop
+/// `redemption_paths` list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedemptionPathPolicy {
+    pub id: String,
+    pub path_type: RedemptionPathType,
+    /// Fiat-equivalent value of one token when redeemed to this path.
+    pub conversion_rate_cig_impact_to_fiat_equivalent: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]